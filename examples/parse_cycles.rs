@@ -15,7 +15,7 @@ fn main() -> Result<(), anyhow::Error> {
     let lines: Vec<_> = res
         .lines()
         .filter(|x| !x.is_empty() && x.len() > 16 && x.contains("EGLL"))
-        .map(|x| Metar::parse(x))
+        .map(Metar::parse)
         .collect();
 
     dbg!(lines);
@@ -0,0 +1,470 @@
+use std::fmt;
+use std::marker::PhantomData;
+use std::sync::OnceLock;
+
+use nom::{
+    combinator::{opt, recognize},
+    multi::many0_count,
+    IResult,
+};
+
+use crate::clouds::{parse_cloud_layer, CloudLayer};
+use crate::colour::{parse_colour_code, ColourCode};
+use crate::pressure::{parse_pressure, Pressure};
+use crate::remarks::{parse_remarks, Remarks};
+use crate::runway_state::{parse_runway_state, RunwayState};
+use crate::rvr::{parse_rvr, RunwayVisualRange};
+use crate::supplementary::{parse_supplementary, Supplementary};
+use crate::temperature::{parse_temperature, Temperature};
+use crate::trend::{parse_trend, Trend};
+use crate::visibility::{parse_visibility, Visibility};
+use crate::weather::{check_present_weather, parse_present_weather, PresentWeather};
+use crate::wind::{parse_wind, Wind};
+use crate::{
+    correction, parse_steps, report_kind, report_type, station, time, Metar, MetarError,
+    ReportKind, ReportType, Step, Time, NO_TIME, NO_WIND,
+};
+
+/// A run of consecutive groups of one element, kept as the source slice and
+/// decoded on demand, so that parsing does not allocate a `Vec` per element.
+pub struct Groups<'a, T> {
+    source: &'a str,
+    parser: fn(&str) -> IResult<&str, T>,
+}
+
+impl<'a, T> Groups<'a, T> {
+    fn new(source: &'a str, parser: fn(&str) -> IResult<&str, T>) -> Self {
+        Groups {
+            source: source.trim(),
+            parser,
+        }
+    }
+
+    /// The groups as they appear in the report.
+    pub fn as_str(&self) -> &'a str {
+        self.source
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.source.is_empty()
+    }
+
+    pub fn iter(&self) -> GroupsIter<'a, T> {
+        GroupsIter {
+            rest: self.source,
+            parser: self.parser,
+            element: PhantomData,
+        }
+    }
+}
+
+impl<T> Clone for Groups<'_, T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T> Copy for Groups<'_, T> {}
+
+impl<T> PartialEq for Groups<'_, T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.source == other.source
+    }
+}
+
+impl<T> fmt::Debug for Groups<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("Groups").field(&self.source).finish()
+    }
+}
+
+/// Serializes the groups as they appear in the report, e.g.
+/// `"R24/1200 R06/P2000"`, so that they can be borrowed again.
+#[cfg(feature = "serde")]
+impl<T> serde::Serialize for Groups<'_, T> {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.source)
+    }
+}
+
+/// Borrows serialized groups and checks them as [`MetarRef::parse`] does.
+#[cfg(feature = "serde")]
+fn deserialize_groups<'de, D, C, T>(
+    deserializer: D,
+    check: fn(&str) -> IResult<&str, C>,
+    parser: fn(&str) -> IResult<&str, T>,
+) -> Result<Groups<'de, T>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    let source = <&str as serde::Deserialize>::deserialize(deserializer)?;
+    match checked_groups(check, parser)(source) {
+        Ok((rest, groups)) if rest.trim().is_empty() => Ok(groups),
+        _ => Err(serde::de::Error::invalid_value(
+            serde::de::Unexpected::Str(source),
+            &"a run of groups of one report element",
+        )),
+    }
+}
+
+#[cfg(feature = "serde")]
+macro_rules! impl_deserialize_groups {
+    ($($element:ty => $check:expr, $parser:expr;)*) => {
+        $(
+            impl<'de: 'a, 'a> serde::Deserialize<'de> for Groups<'a, $element> {
+                fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                    deserialize_groups(deserializer, $check, $parser)
+                }
+            }
+        )*
+    };
+}
+
+#[cfg(feature = "serde")]
+impl_deserialize_groups! {
+    RunwayVisualRange => parse_rvr_group, parse_rvr_group;
+    PresentWeather => check_present_weather, parse_present_weather;
+    CloudLayer => parse_cloud_layer, parse_cloud_layer;
+    Pressure => parse_pressure, parse_pressure;
+    RunwayState => parse_runway_state, parse_runway_state;
+}
+
+impl<'a, T> IntoIterator for &Groups<'a, T> {
+    type Item = T;
+    type IntoIter = GroupsIter<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+pub struct GroupsIter<'a, T> {
+    rest: &'a str,
+    parser: fn(&str) -> IResult<&str, T>,
+    element: PhantomData<T>,
+}
+
+impl<'a, T> Iterator for GroupsIter<'a, T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<T> {
+        // The source was validated while parsing, so every group decodes.
+        let (rest, item) = (self.parser)(self.rest).ok()?;
+        self.rest = &self.rest[self.rest.len() - rest.len()..];
+        Some(item)
+    }
+}
+
+fn parse_rvr_group(s: &str) -> IResult<&str, RunwayVisualRange> {
+    parse_rvr(s.trim_start())
+}
+
+/// Recognizes the run of groups `parser` accepts, without collecting them.
+fn groups<'a, T>(
+    parser: fn(&str) -> IResult<&str, T>,
+) -> impl FnMut(&'a str) -> IResult<&'a str, Groups<'a, T>> {
+    checked_groups(parser, parser)
+}
+
+/// Like [`groups`], but recognizes the run with `check`, which accepts the
+/// same groups as `parser` without building them.
+fn checked_groups<'a, C, T>(
+    check: fn(&str) -> IResult<&str, C>,
+    parser: fn(&str) -> IResult<&str, T>,
+) -> impl FnMut(&'a str) -> IResult<&'a str, Groups<'a, T>> {
+    move |s| {
+        let (rest, source) = recognize(many0_count(check))(s)?;
+        Ok((rest, Groups::new(source, parser)))
+    }
+}
+
+/// A report that borrows from the string it was parsed from.
+///
+/// The station and the remarks stay slices of the input, and list elements
+/// are decoded lazily through [`Groups`]. Checking the groups does not
+/// allocate either, so a report without supplementary information or trend
+/// changes, which are decoded eagerly, is parsed without touching the heap,
+/// and a failed parse only allocates the offending token of its
+/// [`MetarError`].
+/// [`MetarRef::to_metar`] converts it to a [`Metar`].
+#[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct MetarRef<'a> {
+    pub kind: Option<ReportKind>,
+    pub corrected: bool,
+    pub report_type: ReportType,
+    pub station: &'a str,
+    pub time: Time,
+    pub wind: Wind,
+    pub visibility: Visibility,
+    pub runway_visual_range: Groups<'a, RunwayVisualRange>,
+    pub weather: Groups<'a, PresentWeather>,
+    pub clouds: Groups<'a, CloudLayer>,
+    pub temperature: Option<Temperature>,
+    pub pressure: Groups<'a, Pressure>,
+    pub supplementary: Supplementary,
+    pub runway_state: Groups<'a, RunwayState>,
+    pub colour: Option<ColourCode>,
+    pub trend: Option<Trend>,
+    /// Runway state groups that some stations append after the trend.
+    pub runway_state_after_trend: Groups<'a, RunwayState>,
+    /// The `RMK` section, without the keyword; see [`MetarRef::remarks`].
+    #[cfg_attr(feature = "serde", serde(borrow))]
+    pub remarks: Option<&'a str>,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    decoded_remarks: RemarksCache,
+}
+
+/// The remarks decoded by the first call to [`MetarRef::remarks`]. It is
+/// derived from the `remarks` slice, so it never makes two reports differ.
+#[derive(Clone, Default)]
+struct RemarksCache(OnceLock<Remarks>);
+
+impl PartialEq for RemarksCache {
+    fn eq(&self, _: &Self) -> bool {
+        true
+    }
+}
+
+impl fmt::Debug for RemarksCache {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("RemarksCache")
+    }
+}
+
+impl<'a> MetarRef<'a> {
+    /// Parses `s` with the same rules and errors as [`Metar::parse`].
+    pub fn parse(s: &'a str) -> Result<MetarRef<'a>, MetarError> {
+        let mut metar = MetarRef {
+            kind: None,
+            corrected: false,
+            report_type: ReportType::Manual,
+            station: "",
+            time: NO_TIME,
+            wind: NO_WIND,
+            visibility: Visibility::Missing,
+            runway_visual_range: Groups::new("", parse_rvr_group),
+            weather: Groups::new("", parse_present_weather),
+            clouds: Groups::new("", parse_cloud_layer),
+            temperature: None,
+            pressure: Groups::new("", parse_pressure),
+            supplementary: Supplementary::default(),
+            runway_state: Groups::new("", parse_runway_state),
+            colour: None,
+            trend: None,
+            runway_state_after_trend: Groups::new("", parse_runway_state),
+            remarks: None,
+            decoded_remarks: RemarksCache::default(),
+        };
+        let remarks = parse_steps(s, |step, s| metar.apply(step, s))?;
+        metar.remarks = remarks;
+        Ok(metar)
+    }
+
+    /// Decodes the element of one [`Step`] into the matching field.
+    fn apply(&mut self, step: Step, s: &'a str) -> IResult<&'a str, ()> {
+        let rest = match step {
+            Step::Kind => {
+                let (rest, kind) = report_kind(s)?;
+                self.kind = kind;
+                rest
+            }
+            Step::CorrectionBeforeStation
+            | Step::CorrectionAfterTime
+            | Step::CorrectionAfterType => {
+                let (rest, corrected) = correction(s)?;
+                self.corrected |= corrected;
+                rest
+            }
+            Step::Station => {
+                let (rest, station) = station(s)?;
+                self.station = station;
+                rest
+            }
+            Step::Time => {
+                let (rest, (time, _)) = time(s)?;
+                self.time = time;
+                rest
+            }
+            Step::ReportType => {
+                let (rest, report_type) = report_type(s)?;
+                self.report_type = report_type;
+                rest
+            }
+            Step::Wind => {
+                let (rest, wind) = parse_wind(s)?;
+                self.wind = wind;
+                rest
+            }
+            Step::Visibility => {
+                let (rest, visibility) = parse_visibility(s)?;
+                self.visibility = visibility;
+                rest
+            }
+            Step::Rvr => {
+                let (rest, runway_visual_range) = groups(parse_rvr_group)(s)?;
+                self.runway_visual_range = runway_visual_range;
+                rest
+            }
+            Step::Weather => {
+                let (rest, weather) =
+                    checked_groups(check_present_weather, parse_present_weather)(s)?;
+                self.weather = weather;
+                rest
+            }
+            Step::Clouds => {
+                let (rest, clouds) = groups(parse_cloud_layer)(s)?;
+                self.clouds = clouds;
+                rest
+            }
+            Step::Temperature => {
+                let (rest, temperature) = opt(parse_temperature)(s)?;
+                self.temperature = temperature;
+                rest
+            }
+            Step::Pressure => {
+                let (rest, pressure) = groups(parse_pressure)(s)?;
+                self.pressure = pressure;
+                rest
+            }
+            Step::Supplementary => {
+                let (rest, supplementary) = parse_supplementary(s)?;
+                self.supplementary = supplementary;
+                rest
+            }
+            Step::RunwayState => {
+                let (rest, runway_state) = groups(parse_runway_state)(s)?;
+                self.runway_state = runway_state;
+                rest
+            }
+            Step::Colour => {
+                let (rest, colour) = opt(parse_colour_code)(s)?;
+                self.colour = colour;
+                rest
+            }
+            Step::Trend => {
+                let (rest, trend) = opt(parse_trend)(s)?;
+                self.trend = trend;
+                rest
+            }
+            Step::RunwayStateAfterTrend => {
+                let (rest, runway_state) = groups(parse_runway_state)(s)?;
+                self.runway_state_after_trend = runway_state;
+                rest
+            }
+        };
+        Ok((rest, ()))
+    }
+
+    /// Decodes the `RMK` section on the first call, and returns the same
+    /// decoded remarks afterwards, even if `remarks` is changed in between.
+    pub fn remarks(&self) -> &Remarks {
+        self.decoded_remarks.0.get_or_init(|| {
+            self.remarks
+                .and_then(|remarks| parse_remarks(remarks).ok())
+                .map(|(_, remarks)| remarks)
+                .unwrap_or_default()
+        })
+    }
+
+    /// Decodes every element into an owned [`Metar`].
+    pub fn to_metar(&self) -> Metar {
+        Metar {
+            kind: self.kind.clone(),
+            corrected: self.corrected,
+            report_type: self.report_type.clone(),
+            station: self.station.to_owned(),
+            time: self.time.clone(),
+            wind: self.wind.clone(),
+            visibility: self.visibility.clone(),
+            runway_visual_range: self.runway_visual_range.iter().collect(),
+            weather: self.weather.iter().collect(),
+            clouds: self.clouds.iter().collect(),
+            temperature: self.temperature.clone(),
+            pressure: self.pressure.iter().collect(),
+            supplementary: self.supplementary.clone(),
+            runway_state: self
+                .runway_state
+                .iter()
+                .chain(self.runway_state_after_trend.iter())
+                .collect(),
+            colour: self.colour.clone(),
+            trend: self.trend.clone(),
+            remarks: self.remarks().clone(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::remarks::StationType;
+
+    #[test]
+    fn test_to_metar_matches_metar() -> anyhow::Result<()> {
+        for sample in [
+            "Metar LICJ 141600Z 120120G50KT 090V150 CAVOK R04/P1500N R22/P1500U +SN BKN022 OVC050 M04/M07 Q1020 NOSIG 8849//91=",
+            "SPECI KJFK 141651Z COR AUTO 22010KT 1 1/2SM R04R/1000V1600FT -RA BR FEW008 BKN020 12/11 A2992 RMK AO2 SLP132 T01220111 $",
+            "EDDF 141650Z AUTO 24008KT 4000 1500NE BR BKN008 M01/M03 Q1015 RESHSN WS R25L BECMG FM1700 9999 NSW",
+            "LFPO 141630Z AUTO /////KT //// R24///// // //////CB ///// Q////",
+        ] {
+            let borrowed = MetarRef::parse(sample)?;
+            assert_eq!(borrowed.to_metar(), Metar::parse(sample)?);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_borrows_input() -> anyhow::Result<()> {
+        let sample = "LFPG 141630Z 22010KT 9999 R24/1200 R06/P2000 -RA FEW020 BKN040 15/10 Q1013";
+        let metar = MetarRef::parse(sample)?;
+        assert_eq!(metar.station.as_ptr(), sample.as_ptr());
+        assert_eq!(metar.runway_visual_range.as_str(), "R24/1200 R06/P2000");
+        assert_eq!(metar.runway_visual_range.iter().count(), 2);
+        assert_eq!(metar.clouds.as_str(), "FEW020 BKN040");
+        assert!(metar.runway_state.is_empty());
+        assert_eq!(metar.remarks, None);
+        Ok(())
+    }
+
+    #[test]
+    fn test_remarks_are_decoded_once() -> anyhow::Result<()> {
+        let sample = "KJFK 141651Z 22010KT 10SM A2992 RMK AO2 SLP132";
+        let metar = MetarRef::parse(sample)?;
+        let remarks = metar.remarks();
+        assert_eq!(remarks.station_type, Some(StationType::Ao2));
+        assert!(std::ptr::eq(remarks, metar.remarks()));
+        assert_eq!(metar, MetarRef::parse(sample)?);
+        Ok(())
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde_round_trip() -> anyhow::Result<()> {
+        let sample = "KJFK 141651Z 22010KT 1 1/2SM R04R/1000V1600FT -RA BR FEW008 BKN020 12/11 A2992 RMK AO2 SLP132";
+        let metar = MetarRef::parse(sample)?;
+        let json = serde_json::to_string(&metar)?;
+        let value: serde_json::Value = serde_json::from_str(&json)?;
+        assert_eq!(value["weather"], "-RA BR");
+        assert_eq!(value["remarks"], "AO2 SLP132");
+        let deserialized = serde_json::from_str::<MetarRef>(&json)?;
+        assert_eq!(deserialized, metar);
+        assert_eq!(deserialized.remarks(), metar.remarks());
+        assert!(serde_json::from_str::<MetarRef>(&json.replace("-RA BR", "-RA XX1")).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_errors_match_metar() {
+        for sample in [
+            "",
+            "LFPG 141630Z 220X0KT 9999",
+            "LFPG 141630Z 22010KT 9999 XX1",
+        ] {
+            assert_eq!(
+                MetarRef::parse(sample).unwrap_err(),
+                Metar::parse(sample).unwrap_err()
+            );
+        }
+    }
+}
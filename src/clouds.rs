@@ -0,0 +1,375 @@
+use std::fmt;
+use std::str::FromStr;
+
+use nom::{
+    branch::alt,
+    bytes::complete::{tag, take_while_m_n},
+    combinator::{map, map_opt, map_res, opt, value},
+    multi::many0,
+    sequence::{preceded, terminated, tuple},
+    IResult,
+};
+
+use crate::error::{MetarElement, MetarError};
+use crate::{code, decode, end_of_group, Code};
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum CloudCover {
+    Few,
+    Scattered,
+    Broken,
+    Overcast,
+}
+
+impl Code for CloudCover {
+    const ELEMENT: MetarElement = MetarElement::Clouds;
+    const EXPECTED: &'static str = "FEW, SCT, BKN or OVC";
+
+    fn from_code(s: &str) -> Option<Self> {
+        match s {
+            "FEW" => Some(CloudCover::Few),
+            "SCT" => Some(CloudCover::Scattered),
+            "BKN" => Some(CloudCover::Broken),
+            "OVC" => Some(CloudCover::Overcast),
+            _ => None,
+        }
+    }
+}
+
+impl FromStr for CloudCover {
+    type Err = MetarError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        decode(s)
+    }
+}
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum CloudType {
+    Cumulonimbus,
+    ToweringCumulus,
+}
+
+impl Code for CloudType {
+    const ELEMENT: MetarElement = MetarElement::Clouds;
+    const EXPECTED: &'static str = "CB or TCU";
+
+    fn from_code(s: &str) -> Option<Self> {
+        match s {
+            "CB" => Some(CloudType::Cumulonimbus),
+            "TCU" => Some(CloudType::ToweringCumulus),
+            _ => None,
+        }
+    }
+}
+
+impl FromStr for CloudType {
+    type Err = MetarError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        decode(s)
+    }
+}
+
+/// A single cloud group. Heights are expressed in hundreds of feet, as
+/// reported; a `None` cover, height or type stands for an automated `///`.
+#[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(
+    feature = "serde",
+    serde(tag = "type", content = "value", rename_all = "snake_case")
+)]
+pub enum CloudLayer {
+    Layer {
+        cover: Option<CloudCover>,
+        height: Option<u16>,
+        cloud_type: Option<CloudType>,
+    },
+    VerticalVisibility(Option<u16>),
+    NoSignificantCloud,
+    NoCloudDetected,
+    SkyClear,
+    Clear,
+}
+
+impl CloudLayer {
+    /// Height of the layer (or vertical visibility) in feet.
+    pub fn height_feet(&self) -> Option<u32> {
+        match self {
+            CloudLayer::Layer { height, .. } | CloudLayer::VerticalVisibility(height) => {
+                height.map(|h| h as u32 * 100)
+            }
+            _ => None,
+        }
+    }
+}
+
+impl Code for CloudLayer {
+    const ELEMENT: MetarElement = MetarElement::Clouds;
+    const EXPECTED: &'static str = "NSC, NCD, SKC or CLR";
+
+    fn from_code(s: &str) -> Option<Self> {
+        match s {
+            "NSC" => Some(CloudLayer::NoSignificantCloud),
+            "NCD" => Some(CloudLayer::NoCloudDetected),
+            "SKC" => Some(CloudLayer::SkyClear),
+            "CLR" => Some(CloudLayer::Clear),
+            _ => None,
+        }
+    }
+}
+
+/// Parses a whole cloud group, e.g. `BKN022`, `VV002` or `NSC`.
+impl FromStr for CloudLayer {
+    type Err = MetarError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        parse_cloud_layer(s)
+            .ok()
+            .filter(|(rest, _)| rest.is_empty())
+            .map(|(_, layer)| layer)
+            .ok_or_else(|| {
+                MetarError::invalid(
+                    MetarElement::Clouds,
+                    s,
+                    "cloud layer such as BKN022, VV002 or NSC",
+                )
+            })
+    }
+}
+
+impl fmt::Display for CloudCover {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let cover = match self {
+            CloudCover::Few => "FEW",
+            CloudCover::Scattered => "SCT",
+            CloudCover::Broken => "BKN",
+            CloudCover::Overcast => "OVC",
+        };
+        f.write_str(cover)
+    }
+}
+
+impl fmt::Display for CloudType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CloudType::Cumulonimbus => f.write_str("CB"),
+            CloudType::ToweringCumulus => f.write_str("TCU"),
+        }
+    }
+}
+
+fn fmt_height(height: &Option<u16>, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    match height {
+        Some(height) => write!(f, "{:03}", height),
+        None => f.write_str("///"),
+    }
+}
+
+impl fmt::Display for CloudLayer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CloudLayer::Layer {
+                cover,
+                height,
+                cloud_type,
+            } => {
+                match cover {
+                    Some(cover) => write!(f, "{}", cover)?,
+                    None => f.write_str("///")?,
+                }
+                fmt_height(height, f)?;
+                match cloud_type {
+                    Some(cloud_type) => write!(f, "{}", cloud_type),
+                    None => Ok(()),
+                }
+            }
+            CloudLayer::VerticalVisibility(height) => {
+                f.write_str("VV")?;
+                fmt_height(height, f)
+            }
+            CloudLayer::NoSignificantCloud => f.write_str("NSC"),
+            CloudLayer::NoCloudDetected => f.write_str("NCD"),
+            CloudLayer::SkyClear => f.write_str("SKC"),
+            CloudLayer::Clear => f.write_str("CLR"),
+        }
+    }
+}
+
+fn parse_cover(s: &str) -> IResult<&str, Option<CloudCover>> {
+    alt((
+        map_opt(
+            alt((tag("FEW"), tag("SCT"), tag("BKN"), tag("OVC"))),
+            |x: &str| code(x).map(Some),
+        ),
+        value(None, tag("///")),
+    ))(s)
+}
+
+fn parse_height(s: &str) -> IResult<&str, Option<u16>> {
+    alt((
+        map(
+            map_res(
+                take_while_m_n(3, 3, |c: char| c.is_ascii_digit()),
+                str::parse,
+            ),
+            Some,
+        ),
+        value(None, tag("///")),
+    ))(s)
+}
+
+fn parse_cloud_type(s: &str) -> IResult<&str, Option<CloudType>> {
+    map(
+        opt(alt((
+            map_opt(alt((tag("CB"), tag("TCU"))), |x: &str| code(x).map(Some)),
+            value(None, tag("///")),
+        ))),
+        Option::flatten,
+    )(s)
+}
+
+fn parse_layer(s: &str) -> IResult<&str, CloudLayer> {
+    map(
+        tuple((parse_cover, parse_height, parse_cloud_type)),
+        |(cover, height, cloud_type)| CloudLayer::Layer {
+            cover,
+            height,
+            cloud_type,
+        },
+    )(s)
+}
+
+pub fn parse_cloud_layer(s: &str) -> IResult<&str, CloudLayer> {
+    let s = s.trim_start();
+    terminated(
+        alt((
+            map(
+                preceded(tag("VV"), parse_height),
+                CloudLayer::VerticalVisibility,
+            ),
+            map_opt(alt((tag("NSC"), tag("NCD"), tag("SKC"), tag("CLR"))), code),
+            parse_layer,
+        )),
+        end_of_group,
+    )(s)
+}
+
+pub fn parse_clouds(s: &str) -> IResult<&str, Vec<CloudLayer>> {
+    many0(parse_cloud_layer)(s)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_parse_cloud_layer() -> anyhow::Result<()> {
+        assert_eq!(
+            parse_cloud_layer("BKN022")?.1,
+            CloudLayer::Layer {
+                cover: Some(CloudCover::Broken),
+                height: Some(22),
+                cloud_type: None
+            }
+        );
+        assert_eq!(
+            parse_cloud_layer("FEW015CB")?.1,
+            CloudLayer::Layer {
+                cover: Some(CloudCover::Few),
+                height: Some(15),
+                cloud_type: Some(CloudType::Cumulonimbus)
+            }
+        );
+        assert_eq!(
+            parse_cloud_layer("SCT030TCU")?.1,
+            CloudLayer::Layer {
+                cover: Some(CloudCover::Scattered),
+                height: Some(30),
+                cloud_type: Some(CloudType::ToweringCumulus)
+            }
+        );
+        assert_eq!(
+            parse_cloud_layer("//////CB")?.1,
+            CloudLayer::Layer {
+                cover: None,
+                height: None,
+                cloud_type: Some(CloudType::Cumulonimbus)
+            }
+        );
+        assert_eq!(
+            parse_cloud_layer("OVC008///")?.1,
+            CloudLayer::Layer {
+                cover: Some(CloudCover::Overcast),
+                height: Some(8),
+                cloud_type: None
+            }
+        );
+        assert_eq!(
+            parse_cloud_layer("VV003")?.1,
+            CloudLayer::VerticalVisibility(Some(3))
+        );
+        assert_eq!(
+            parse_cloud_layer("VV///")?.1,
+            CloudLayer::VerticalVisibility(None)
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_sky_condition_codes() -> anyhow::Result<()> {
+        assert_eq!(parse_cloud_layer("NSC")?.1, CloudLayer::NoSignificantCloud);
+        assert_eq!(parse_cloud_layer("NCD")?.1, CloudLayer::NoCloudDetected);
+        assert_eq!(parse_cloud_layer("SKC")?.1, CloudLayer::SkyClear);
+        assert_eq!(parse_cloud_layer("CLR")?.1, CloudLayer::Clear);
+        assert!(parse_cloud_layer("NOSIG").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_cloud_layer_from_str() -> anyhow::Result<()> {
+        assert_eq!(
+            "BKN022".parse::<CloudLayer>()?,
+            CloudLayer::Layer {
+                cover: Some(CloudCover::Broken),
+                height: Some(22),
+                cloud_type: None
+            }
+        );
+        assert_eq!("VV002".parse::<CloudLayer>()?.height_feet(), Some(200));
+        assert_eq!("NSC".parse::<CloudLayer>()?, CloudLayer::NoSignificantCloud);
+        assert!("BKN022 OVC050".parse::<CloudLayer>().is_err());
+        assert!("NOSIG".parse::<CloudLayer>().is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_clouds() -> anyhow::Result<()> {
+        let (rest, clouds) = parse_clouds(" BKN022 OVC050 M04/M07")?;
+        assert_eq!(rest, " M04/M07");
+        assert_eq!(clouds.len(), 2);
+        assert_eq!(clouds[1].height_feet(), Some(5000));
+        Ok(())
+    }
+
+    #[test]
+    fn test_display_cloud_layer() -> anyhow::Result<()> {
+        for sample in [
+            "FEW020",
+            "BKN008CB",
+            "OVC100TCU",
+            "VV002",
+            "VV///",
+            "//////CB",
+            "NSC",
+            "CLR",
+        ] {
+            assert_eq!(parse_cloud_layer(sample)?.1.to_string(), sample);
+        }
+        Ok(())
+    }
+}
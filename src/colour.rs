@@ -0,0 +1,232 @@
+use std::fmt;
+use std::str::FromStr;
+
+use nom::{
+    branch::alt,
+    bytes::complete::tag,
+    combinator::{map_opt, opt},
+    sequence::{pair, terminated},
+    IResult,
+};
+
+use crate::clouds::{CloudCover, CloudLayer};
+use crate::error::{MetarElement, MetarError};
+use crate::visibility::Visibility;
+use crate::{code, decode, end_of_group, Code};
+
+/// NATO aerodrome colour state, ordered from best (`Blue`) to worst (`Red`).
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum ColourState {
+    Blue,
+    White,
+    Green,
+    Yellow,
+    Amber,
+    Red,
+}
+
+impl ColourState {
+    /// Minimum visibility in meters and cloud base in feet for each state.
+    fn minima(&self) -> (f64, u32) {
+        match self {
+            ColourState::Blue => (8000.0, 2500),
+            ColourState::White => (5000.0, 1500),
+            ColourState::Green => (3700.0, 700),
+            ColourState::Yellow => (1600.0, 300),
+            ColourState::Amber => (800.0, 200),
+            ColourState::Red => (0.0, 0),
+        }
+    }
+
+    /// Derives the expected colour state from the prevailing visibility and
+    /// the lowest cloud base covering at least 3/8 of the sky (SCT or more).
+    /// Vertical visibility counts as a cloud base.
+    pub fn derive(visibility: &Visibility, clouds: &[CloudLayer]) -> Option<ColourState> {
+        let visibility = visibility.meters()?;
+        let cloud_base = clouds
+            .iter()
+            .filter(|layer| match layer {
+                CloudLayer::Layer { cover, .. } => {
+                    matches!(cover, Some(cover) if *cover != CloudCover::Few)
+                }
+                CloudLayer::VerticalVisibility(_) => true,
+                _ => false,
+            })
+            .filter_map(CloudLayer::height_feet)
+            .min();
+
+        [
+            ColourState::Blue,
+            ColourState::White,
+            ColourState::Green,
+            ColourState::Yellow,
+            ColourState::Amber,
+        ]
+        .into_iter()
+        .find(|state| {
+            let (min_visibility, min_cloud_base) = state.minima();
+            visibility >= min_visibility && cloud_base.map_or(true, |b| b >= min_cloud_base)
+        })
+        .or(Some(ColourState::Red))
+    }
+}
+
+impl Code for ColourState {
+    const ELEMENT: MetarElement = MetarElement::Colour;
+    const EXPECTED: &'static str = "BLU, WHT, GRN, YLO, AMB or RED";
+
+    fn from_code(s: &str) -> Option<Self> {
+        match s {
+            "BLU" => Some(ColourState::Blue),
+            "WHT" => Some(ColourState::White),
+            "GRN" => Some(ColourState::Green),
+            "YLO" => Some(ColourState::Yellow),
+            "AMB" => Some(ColourState::Amber),
+            "RED" => Some(ColourState::Red),
+            _ => None,
+        }
+    }
+}
+
+impl FromStr for ColourState {
+    type Err = MetarError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        decode(s)
+    }
+}
+
+#[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ColourCode {
+    pub state: ColourState,
+    /// `BLACK` prefix: the airfield is closed for reasons other than weather.
+    pub black: bool,
+}
+
+impl fmt::Display for ColourState {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let state = match self {
+            ColourState::Blue => "BLU",
+            ColourState::White => "WHT",
+            ColourState::Green => "GRN",
+            ColourState::Yellow => "YLO",
+            ColourState::Amber => "AMB",
+            ColourState::Red => "RED",
+        };
+        f.write_str(state)
+    }
+}
+
+impl fmt::Display for ColourCode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.black {
+            f.write_str("BLACK")?;
+        }
+        write!(f, "{}", self.state)
+    }
+}
+
+pub fn parse_colour_code(s: &str) -> IResult<&str, ColourCode> {
+    let s = s.trim_start();
+    let (rest, (black, state)) = terminated(
+        pair(
+            opt(tag("BLACK")),
+            map_opt(
+                alt((
+                    tag("BLU"),
+                    tag("WHT"),
+                    tag("GRN"),
+                    tag("YLO"),
+                    tag("AMB"),
+                    tag("RED"),
+                )),
+                code,
+            ),
+        ),
+        end_of_group,
+    )(s)?;
+
+    Ok((
+        rest,
+        ColourCode {
+            state,
+            black: black.is_some(),
+        },
+    ))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::visibility::VisibilityDistance;
+
+    #[test]
+    fn test_parse_colour_code() -> anyhow::Result<()> {
+        assert_eq!(
+            parse_colour_code("BLU")?.1,
+            ColourCode {
+                state: ColourState::Blue,
+                black: false
+            }
+        );
+        assert_eq!(
+            parse_colour_code(" BLACKAMB")?.1,
+            ColourCode {
+                state: ColourState::Amber,
+                black: true
+            }
+        );
+        assert!(parse_colour_code("BLUE").is_err());
+        assert!(parse_colour_code("BLACK").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_derive_colour_state() {
+        let layer = |cover, height| CloudLayer::Layer {
+            cover: Some(cover),
+            height: Some(height),
+            cloud_type: None,
+        };
+        assert_eq!(
+            ColourState::derive(&Visibility::Cavok, &[]),
+            Some(ColourState::Blue)
+        );
+        assert_eq!(
+            ColourState::derive(
+                &VisibilityDistance::Meters(9999).into(),
+                &[layer(CloudCover::Few, 5), layer(CloudCover::Broken, 20)]
+            ),
+            Some(ColourState::White)
+        );
+        assert_eq!(
+            ColourState::derive(&VisibilityDistance::Meters(4000).into(), &[]),
+            Some(ColourState::Green)
+        );
+        assert_eq!(
+            ColourState::derive(
+                &VisibilityDistance::Meters(9999).into(),
+                &[layer(CloudCover::Overcast, 2)]
+            ),
+            Some(ColourState::Amber)
+        );
+        assert_eq!(
+            ColourState::derive(
+                &VisibilityDistance::Meters(600).into(),
+                &[CloudLayer::VerticalVisibility(Some(1))]
+            ),
+            Some(ColourState::Red)
+        );
+    }
+
+    #[test]
+    fn test_display_colour_code() -> anyhow::Result<()> {
+        for sample in ["BLU", "BLACKAMB", "RED"] {
+            assert_eq!(parse_colour_code(sample)?.1.to_string(), sample);
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,219 @@
+use std::borrow::Cow;
+use std::fmt;
+
+/// Report element an error refers to.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum MetarElement {
+    /// `METAR`/`SPECI`, `COR`, `AUTO` and `NIL`.
+    Header,
+    Station,
+    Time,
+    Wind,
+    Visibility,
+    Rvr,
+    Weather,
+    Clouds,
+    Temperature,
+    Pressure,
+    Supplementary,
+    RunwayState,
+    Colour,
+    Trend,
+    Remarks,
+    /// A group that does not look like any element.
+    Unknown,
+}
+
+impl MetarElement {
+    /// Best guess at the element a stray group was meant to be, from its shape.
+    pub(crate) fn guess(token: &str) -> MetarElement {
+        let bytes = token.as_bytes();
+        let digits = |range: std::ops::Range<usize>| {
+            bytes
+                .get(range)
+                .is_some_and(|b| b.iter().all(u8::is_ascii_digit))
+        };
+
+        if bytes.first() == Some(&b'R') && digits(1..3) {
+            match token.split_once('/') {
+                Some((_, state)) if state.len() == 6 => MetarElement::RunwayState,
+                _ => MetarElement::Rvr,
+            }
+        } else if ["KT", "MPS", "KMH", "MPH"]
+            .iter()
+            .any(|unit| token.ends_with(unit))
+        {
+            MetarElement::Wind
+        } else if token.ends_with("SM") || (token.len() == 4 && digits(0..4)) {
+            MetarElement::Visibility
+        } else if ["FEW", "SCT", "BKN", "OVC", "VV"]
+            .iter()
+            .any(|cover| token.starts_with(cover))
+        {
+            MetarElement::Clouds
+        } else if (token.starts_with('Q') || token.starts_with('A')) && digits(1..5) {
+            MetarElement::Pressure
+        } else {
+            MetarElement::Unknown
+        }
+    }
+}
+
+impl fmt::Display for MetarElement {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            MetarElement::Header => "report header",
+            MetarElement::Station => "station",
+            MetarElement::Time => "time",
+            MetarElement::Wind => "wind",
+            MetarElement::Visibility => "visibility",
+            MetarElement::Rvr => "runway visual range",
+            MetarElement::Weather => "present weather",
+            MetarElement::Clouds => "clouds",
+            MetarElement::Temperature => "temperature",
+            MetarElement::Pressure => "pressure",
+            MetarElement::Supplementary => "supplementary information",
+            MetarElement::RunwayState => "runway state",
+            MetarElement::Colour => "colour state",
+            MetarElement::Trend => "trend",
+            MetarElement::Remarks => "remarks",
+            MetarElement::Unknown => "unknown group",
+        };
+        f.write_str(name)
+    }
+}
+
+/// Error returned when a report, or one of its elements, cannot be decoded.
+///
+/// Offsets are in bytes from the start of the string handed to the parser:
+/// the whole report for [`Metar::parse`](crate::Metar::parse), the code
+/// itself for an element's `FromStr` impl.
+///
+/// `expected` borrows a static description when the error comes from the
+/// parser, and owns it once the error has been deserialized.
+#[derive(Debug, PartialEq, Eq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(
+    feature = "serde",
+    serde(tag = "type", content = "value", rename_all = "snake_case")
+)]
+pub enum MetarError {
+    /// The report ended before a mandatory element.
+    Missing {
+        element: MetarElement,
+        offset: usize,
+        expected: Cow<'static, str>,
+    },
+    /// A group could not be decoded as the element expected at its position.
+    Invalid {
+        element: MetarElement,
+        offset: usize,
+        token: String,
+        expected: Cow<'static, str>,
+    },
+}
+
+impl MetarError {
+    /// Error for a code that an element's `FromStr` impl does not know.
+    ///
+    /// `FromStr` only sees the code itself, so the offset is relative to it
+    /// and always `0`. [`Metar::parse`](crate::Metar::parse) reports the
+    /// position of the enclosing group instead.
+    pub(crate) fn invalid(element: MetarElement, token: &str, expected: &'static str) -> Self {
+        MetarError::Invalid {
+            element,
+            offset: 0,
+            token: token.to_owned(),
+            expected: expected.into(),
+        }
+    }
+
+    pub fn element(&self) -> MetarElement {
+        match self {
+            MetarError::Missing { element, .. } | MetarError::Invalid { element, .. } => *element,
+        }
+    }
+
+    pub fn offset(&self) -> usize {
+        match self {
+            MetarError::Missing { offset, .. } | MetarError::Invalid { offset, .. } => *offset,
+        }
+    }
+
+    /// The offending group, empty when the report ended early.
+    pub fn token(&self) -> &str {
+        match self {
+            MetarError::Missing { .. } => "",
+            MetarError::Invalid { token, .. } => token,
+        }
+    }
+
+    pub fn expected(&self) -> &str {
+        match self {
+            MetarError::Missing { expected, .. } | MetarError::Invalid { expected, .. } => expected,
+        }
+    }
+}
+
+impl fmt::Display for MetarError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MetarError::Missing {
+                element,
+                offset,
+                expected,
+            } => write!(
+                f,
+                "missing {} at byte {}: expected {}",
+                element, offset, expected
+            ),
+            MetarError::Invalid {
+                element,
+                offset,
+                token,
+                expected,
+            } => write!(
+                f,
+                "invalid {} {:?} at byte {}: expected {}",
+                element, token, offset, expected
+            ),
+        }
+    }
+}
+
+impl std::error::Error for MetarError {}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_guess_element() {
+        assert_eq!(MetarElement::guess("R24/ABCD"), MetarElement::Rvr);
+        assert_eq!(MetarElement::guess("R24/CLRD9Z"), MetarElement::RunwayState);
+        assert_eq!(MetarElement::guess("220X0KT"), MetarElement::Wind);
+        assert_eq!(MetarElement::guess("1/0SM"), MetarElement::Visibility);
+        assert_eq!(MetarElement::guess("BKN0X0"), MetarElement::Clouds);
+        assert_eq!(MetarElement::guess("Q101"), MetarElement::Unknown);
+        assert_eq!(MetarElement::guess("A2992X"), MetarElement::Pressure);
+        assert_eq!(MetarElement::guess("XYZ"), MetarElement::Unknown);
+    }
+
+    #[test]
+    fn test_display() {
+        let error = MetarError::Invalid {
+            element: MetarElement::Wind,
+            offset: 12,
+            token: "220X0KT".to_owned(),
+            expected: "wind group such as 22010KT".into(),
+        };
+        assert_eq!(
+            error.to_string(),
+            "invalid wind \"220X0KT\" at byte 12: expected wind group such as 22010KT"
+        );
+        assert_eq!(error.element(), MetarElement::Wind);
+        assert_eq!(error.token(), "220X0KT");
+    }
+}
@@ -1,51 +1,695 @@
+use std::fmt;
+use std::ops::Range;
 use std::str::FromStr;
 
+use clouds::{parse_cloud_layer, CloudLayer};
+use colour::{parse_colour_code, ColourCode, ColourState};
+pub use error::{MetarElement, MetarError};
 use nom::branch::alt;
-use nom::bytes::complete::tag;
-use nom::combinator::{map_res, opt};
-use nom::multi::count;
-use nom::sequence::tuple;
+use nom::bytes::complete::{tag, tag_no_case, take_while_m_n};
+use nom::character::complete::{multispace0, multispace1};
+use nom::combinator::{consumed, eof, map, map_opt, map_res, opt, peek, value};
+use nom::multi::{fold_many0, many0};
+use nom::sequence::{preceded, terminated, tuple};
 use nom::{bytes::complete::take, IResult};
-use rvr::RunwayVisualRange;
+use pressure::{parse_pressure, Pressure};
+use remarks::{parse_remark, split_remarks, Remarks};
+use runway_state::{parse_runway_state, RunwayState};
+use rvr::{parse_rvr, RunwayVisualRange};
+use sea_state::parse_sea_state;
+use sensor::{ElementStatus, SensorKind};
+use supplementary::{parse_recent_weather, parse_wind_shear, Supplementary};
+use temperature::{parse_temperature, Temperature};
+use trend::{parse_nosig, parse_trend_change, Trend};
 use visibility::{parse_visibility, Visibility};
-use wind::{parse_wind, Wind};
+use weather::{
+    parse_present_weather, PhenomenonCategory, PresentWeather, WeatherDescriptor, WeatherPhenomenon,
+};
+use wind::{parse_wind, Wind, WindDirection, WindSpeed, WindUnit};
+pub mod borrowed;
+pub mod clouds;
+pub mod colour;
+pub mod error;
+pub mod pressure;
+pub mod remarks;
+pub mod runway_state;
 pub mod rvr;
+pub mod sea_state;
+pub mod sensor;
+pub mod supplementary;
+pub mod temperature;
+pub mod trend;
 pub mod visibility;
+pub mod weather;
 pub mod wind;
 
-fn parse_with_bounds(min: u8, max: u8, s: &str) -> anyhow::Result<u8> {
-    match s.parse::<u8>() {
-        Ok(d) if d >= min && d <= max => Ok(d),
-        Ok(_) => Err(anyhow::anyhow!("Value out of bounds")),
-        Err(e) => Err(e.into()),
+fn parse_with_bounds(min: u8, max: u8, s: &str) -> Option<u8> {
+    s.parse::<u8>().ok().filter(|d| (min..=max).contains(d))
+}
+
+/// A code from a fixed table, such as `BKN` or `TS`.
+///
+/// Parsers try many alternatives that are expected to fail, so they decode
+/// through [`code`], which fails without building a `MetarError`. The error
+/// for a whole group is built once, by [`Cursor`]. The public `FromStr` impls
+/// go through [`decode`] instead.
+pub(crate) trait Code: Sized {
+    const ELEMENT: MetarElement;
+    const EXPECTED: &'static str;
+
+    fn from_code(s: &str) -> Option<Self>;
+}
+
+/// Decodes a code inside a parser, e.g. `map_opt(take(2usize), code)`.
+pub(crate) fn code<T: Code>(s: &str) -> Option<T> {
+    T::from_code(s)
+}
+
+/// Decodes a code for a `FromStr` impl.
+pub(crate) fn decode<T: Code>(s: &str) -> Result<T, MetarError> {
+    T::from_code(s).ok_or_else(|| MetarError::invalid(T::ELEMENT, s, T::EXPECTED))
+}
+
+fn station(s: &str) -> IResult<&str, &str> {
+    terminated(
+        take_while_m_n(4, 4, |c: char| c.is_ascii_alphanumeric()),
+        end_of_group,
+    )(s.trim_start())
+}
+
+/// Succeeds without consuming anything when `s` is at the end of a group,
+/// so that a code-based parser cannot match a prefix of an unrelated group.
+pub(crate) fn end_of_group(s: &str) -> IResult<&str, ()> {
+    value((), peek(alt((multispace1, eof, tag("=")))))(s)
+}
+
+/// Like `many0`, but the `Vec` is only allocated once `parser` matches, so
+/// that trying an absent element does not touch the heap.
+pub(crate) fn lazy_many0<'a, O>(
+    parser: impl FnMut(&'a str) -> IResult<&'a str, O>,
+) -> impl FnMut(&'a str) -> IResult<&'a str, Vec<O>> {
+    fold_many0(parser, Vec::new, |mut items, item| {
+        items.push(item);
+        items
+    })
+}
+
+/// Writes groups separated by single spaces, straight to the formatter.
+/// Absent elements are left out by passing them as an empty `Option` or
+/// slice to [`GroupWriter::groups`].
+pub(crate) struct GroupWriter<'a, 'b> {
+    f: &'a mut fmt::Formatter<'b>,
+    separator: &'static str,
+}
+
+impl<'a, 'b> GroupWriter<'a, 'b> {
+    pub(crate) fn new(f: &'a mut fmt::Formatter<'b>) -> Self {
+        GroupWriter { f, separator: "" }
+    }
+
+    pub(crate) fn group(&mut self, group: impl fmt::Display) -> fmt::Result {
+        write!(self.f, "{}{}", self.separator, group)?;
+        self.separator = " ";
+        Ok(())
+    }
+
+    pub(crate) fn groups<T: fmt::Display>(
+        &mut self,
+        groups: impl IntoIterator<Item = T>,
+    ) -> fmt::Result {
+        groups.into_iter().try_for_each(|group| self.group(group))
+    }
+
+    /// Writes an element that may consist of no group at all.
+    pub(crate) fn section(&mut self, section: &impl WriteGroups) -> fmt::Result {
+        section.write_groups(self)
     }
 }
-fn take4(s: &str) -> IResult<&str, &str> {
-    take(4usize)(s)
+
+/// An element made of any number of groups, such as the supplementary
+/// information, written through a [`GroupWriter`] so that it joins the
+/// surrounding groups without a stray space when empty.
+pub(crate) trait WriteGroups {
+    fn write_groups(&self, w: &mut GroupWriter<'_, '_>) -> fmt::Result;
 }
 
 fn time(s: &str) -> IResult<&str, (Time, &str)> {
     let s = s.trim_start();
-    let take2 = take(2usize);
-    let time_component = map_res(count(take2, 3), |v| Time::from_vec(v));
+    let take2 = || take(2usize);
+    let time_component = map_opt(tuple((take2(), take2(), take2())), |(day, hour, minute)| {
+        Time::from_digits(day, hour, minute)
+    });
     let mut time_parser = tuple((time_component, tag("Z")));
     time_parser(s)
 }
 
+fn report_kind(s: &str) -> IResult<&str, Option<ReportKind>> {
+    let parser = alt((tag_no_case("METAR"), tag_no_case("SPECI")));
+    opt(map_opt(terminated(parser, multispace1), code))(s.trim_start())
+}
+
+fn correction(s: &str) -> IResult<&str, bool> {
+    map(opt(terminated(tag("COR"), end_of_group)), |c| c.is_some())(s.trim_start())
+}
+
 fn report_type(s: &str) -> IResult<&str, ReportType> {
     let parser = opt(alt((tag("AUTO"), tag("NIL"))));
     map_res(parser, |x: Option<&str>| x.unwrap_or("").parse())(s.trim_start())
 }
 
-#[derive(Debug, PartialEq)]
-enum ReportType {
+/// Walks the report body element by element, turning parser failures into
+/// `MetarError`s that point at the offending group.
+struct Cursor<'a> {
+    input: &'a str,
+    rest: &'a str,
+}
+
+impl<'a> Cursor<'a> {
+    fn new(input: &'a str, body: &'a str) -> Self {
+        Cursor { input, rest: body }
+    }
+
+    /// Byte offset of `at`, which must be a slice of the input.
+    fn offset(&self, at: &str) -> usize {
+        at.as_ptr() as usize - self.input.as_ptr() as usize
+    }
+
+    fn parse<T>(
+        &mut self,
+        element: MetarElement,
+        expected: &'static str,
+        mut parser: impl FnMut(&'a str) -> IResult<&'a str, T>,
+    ) -> Result<T, MetarError> {
+        match parser(self.rest) {
+            Ok((rest, value)) => {
+                self.rest = rest;
+                Ok(value)
+            }
+            Err(_) => Err(self.error(element, expected)),
+        }
+    }
+
+    fn step<T>(
+        &mut self,
+        step: Step,
+        parser: impl FnMut(&'a str) -> IResult<&'a str, T>,
+    ) -> Result<T, MetarError> {
+        self.parse(step.element(), step.expected(), parser)
+    }
+
+    fn error(&self, element: MetarElement, expected: &'static str) -> MetarError {
+        let group = self.rest.trim_start();
+        match group.split_whitespace().next() {
+            Some(token) => MetarError::Invalid {
+                element,
+                offset: self.offset(group),
+                token: token.to_owned(),
+                expected: expected.into(),
+            },
+            None => MetarError::Missing {
+                element,
+                offset: self.offset(group),
+                expected: expected.into(),
+            },
+        }
+    }
+
+    /// Runs `parser` and moves past what it consumed. Returns whether it
+    /// matched at least one group.
+    fn advance(&mut self, mut parser: impl FnMut(&'a str) -> IResult<&'a str, ()>) -> bool {
+        match parser(self.rest) {
+            Ok((rest, _)) if rest.trim_start().len() < self.rest.trim_start().len() => {
+                self.rest = rest;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    fn is_empty(&self) -> bool {
+        self.rest.trim().is_empty()
+    }
+
+    /// Moves past the next group and returns it.
+    fn skip(&mut self) -> &'a str {
+        let group = self.rest.trim_start();
+        let end = group.find(char::is_whitespace).unwrap_or(group.len());
+        self.rest = &group[end..];
+        &group[..end]
+    }
+
+    fn unexpected(&self) -> MetarError {
+        let token = self.rest.split_whitespace().next().unwrap_or_default();
+        self.error(MetarElement::guess(token), "end of report or RMK")
+    }
+
+    /// Fails on the first group left over once every element has been tried.
+    fn finish(&self) -> Result<(), MetarError> {
+        match self.is_empty() {
+            true => Ok(()),
+            false => Err(self.unexpected()),
+        }
+    }
+}
+
+/// Runs every step of [`Step::ALL`] over the body of `s`, handing each one to
+/// `apply`, and returns the `RMK` section. [`Metar::parse`] and
+/// [`MetarRef::parse`](borrowed::MetarRef::parse) both go through here, so
+/// that they accept the same reports and fail with the same errors.
+fn parse_steps<'a>(
+    s: &'a str,
+    mut apply: impl FnMut(Step, &'a str) -> IResult<&'a str, ()>,
+) -> Result<Option<&'a str>, MetarError> {
+    let input = s;
+    let s = s.trim().trim_end_matches('=').trim_end();
+    let (body, remarks) = split_remarks(s);
+    let mut cursor = Cursor::new(input, body);
+    for step in Step::ALL {
+        cursor.step(step, |s| apply(step, s))?;
+    }
+    cursor.finish()?;
+    Ok(remarks)
+}
+
+/// Stand-ins for mandatory elements until they are decoded, and for good in
+/// lenient mode when they cannot be found.
+const NO_TIME: Time = Time {
+    day: 0,
+    hour: 0,
+    minute: 0,
+};
+const NO_WIND: Wind = Wind {
+    direction: WindDirection::Missing,
+    speed: WindSpeed::Missing,
+    gust_speed: None,
+    unit: WindUnit::Kt,
+    variable_direction: None,
+};
+
+/// The report elements, in the order they appear in the body.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Step {
+    Kind,
+    CorrectionBeforeStation,
+    Station,
+    Time,
+    CorrectionAfterTime,
+    ReportType,
+    CorrectionAfterType,
+    Wind,
+    Visibility,
+    Rvr,
+    Weather,
+    Clouds,
+    Temperature,
+    Pressure,
+    Supplementary,
+    RunwayState,
+    Colour,
+    Trend,
+    /// Some stations append runway state groups after the trend.
+    RunwayStateAfterTrend,
+}
+
+impl Step {
+    const ALL: [Step; 19] = [
+        Step::Kind,
+        Step::CorrectionBeforeStation,
+        Step::Station,
+        Step::Time,
+        Step::CorrectionAfterTime,
+        Step::ReportType,
+        Step::CorrectionAfterType,
+        Step::Wind,
+        Step::Visibility,
+        Step::Rvr,
+        Step::Weather,
+        Step::Clouds,
+        Step::Temperature,
+        Step::Pressure,
+        Step::Supplementary,
+        Step::RunwayState,
+        Step::Colour,
+        Step::Trend,
+        Step::RunwayStateAfterTrend,
+    ];
+
+    fn index(self) -> usize {
+        self as usize
+    }
+
+    fn element(self) -> MetarElement {
+        match self {
+            Step::Kind
+            | Step::CorrectionBeforeStation
+            | Step::CorrectionAfterTime
+            | Step::ReportType
+            | Step::CorrectionAfterType => MetarElement::Header,
+            Step::Station => MetarElement::Station,
+            Step::Time => MetarElement::Time,
+            Step::Wind => MetarElement::Wind,
+            Step::Visibility => MetarElement::Visibility,
+            Step::Rvr => MetarElement::Rvr,
+            Step::Weather => MetarElement::Weather,
+            Step::Clouds => MetarElement::Clouds,
+            Step::Temperature => MetarElement::Temperature,
+            Step::Pressure => MetarElement::Pressure,
+            Step::Supplementary => MetarElement::Supplementary,
+            Step::RunwayState | Step::RunwayStateAfterTrend => MetarElement::RunwayState,
+            Step::Colour => MetarElement::Colour,
+            Step::Trend => MetarElement::Trend,
+        }
+    }
+
+    fn expected(self) -> &'static str {
+        match self {
+            Step::Kind => "METAR or SPECI",
+            Step::CorrectionBeforeStation
+            | Step::CorrectionAfterTime
+            | Step::CorrectionAfterType => "COR",
+            Step::Station => "four-character ICAO identifier",
+            Step::Time => "day and time such as 141600Z",
+            Step::ReportType => "AUTO or NIL",
+            Step::Wind => "wind group such as 22010KT",
+            Step::Visibility => "visibility such as 9999, 1/2SM or CAVOK",
+            Step::Rvr => "runway visual range such as R24/1200",
+            Step::Weather => "present weather such as -RA",
+            Step::Clouds => "cloud group such as BKN020",
+            Step::Temperature => "temperature such as M04/M07",
+            Step::Pressure => "pressure such as Q1013",
+            Step::Supplementary => "recent weather, wind shear or sea state",
+            Step::RunwayState | Step::RunwayStateAfterTrend => "runway state such as R24/290350",
+            Step::Colour => "colour state such as BLU",
+            Step::Trend => "NOSIG, BECMG or TEMPO",
+        }
+    }
+
+    fn is_mandatory(self) -> bool {
+        matches!(
+            self,
+            Step::Station | Step::Time | Step::Wind | Step::Visibility
+        )
+    }
+
+    /// Whether the element is a list of groups that can continue after a
+    /// skipped one.
+    fn is_repeated(self) -> bool {
+        matches!(
+            self,
+            Step::Rvr
+                | Step::Weather
+                | Step::Clouds
+                | Step::Pressure
+                | Step::RunwayState
+                | Step::RunwayStateAfterTrend
+        )
+    }
+}
+
+/// Elements decoded so far, filled in one step at a time, along with the
+/// span each one came from.
+struct Elements<'a> {
+    input: &'a str,
+    spans: MetarSpans,
+    kind: Option<ReportKind>,
+    corrected: bool,
+    report_type: Option<ReportType>,
+    station: Option<String>,
+    time: Option<Time>,
+    wind: Option<Wind>,
+    visibility: Option<Visibility>,
+    runway_visual_range: Vec<RunwayVisualRange>,
+    weather: Vec<PresentWeather>,
+    clouds: Vec<CloudLayer>,
+    temperature: Option<Temperature>,
+    pressure: Vec<Pressure>,
+    supplementary: Supplementary,
+    runway_state: Vec<RunwayState>,
+    colour: Option<ColourCode>,
+    trend: Option<Trend>,
+}
+
+impl<'a> Elements<'a> {
+    fn new(input: &'a str) -> Self {
+        Elements {
+            input,
+            spans: MetarSpans::default(),
+            kind: None,
+            corrected: false,
+            report_type: None,
+            station: None,
+            time: None,
+            wind: None,
+            visibility: None,
+            runway_visual_range: vec![],
+            weather: vec![],
+            clouds: vec![],
+            temperature: None,
+            pressure: vec![],
+            supplementary: Supplementary::default(),
+            runway_state: vec![],
+            colour: None,
+            trend: None,
+        }
+    }
+
+    /// Span of a consumed slice of the input, without surrounding
+    /// whitespace. `None` when the parser matched nothing.
+    fn span(&self, consumed: &str) -> Option<Span> {
+        let group = consumed.trim();
+        let start = group.as_ptr() as usize - self.input.as_ptr() as usize;
+        (!group.is_empty()).then_some(start..start + group.len())
+    }
+
+    fn apply(&mut self, step: Step, s: &'a str) -> IResult<&'a str, ()> {
+        let rest = match step {
+            Step::Kind => {
+                let (rest, (consumed, kind)) = consumed(report_kind)(s)?;
+                if kind.is_some() {
+                    self.kind = kind;
+                    self.spans.kind = self.span(consumed);
+                }
+                rest
+            }
+            Step::CorrectionBeforeStation
+            | Step::CorrectionAfterTime
+            | Step::CorrectionAfterType => {
+                let (rest, (consumed, corrected)) = consumed(correction)(s)?;
+                if corrected {
+                    self.corrected = true;
+                    self.spans.corrected = self.span(consumed);
+                }
+                rest
+            }
+            Step::Station => {
+                let (rest, (consumed, station)) = consumed(station)(s)?;
+                self.station = Some(station.to_owned());
+                self.spans.station = self.span(consumed);
+                rest
+            }
+            Step::Time => {
+                let (rest, (consumed, (time, _))) = consumed(time)(s)?;
+                self.time = Some(time);
+                self.spans.time = self.span(consumed);
+                rest
+            }
+            Step::ReportType => {
+                let (rest, (consumed, report_type)) = consumed(report_type)(s)?;
+                self.report_type = Some(report_type);
+                self.spans.report_type = self.span(consumed);
+                rest
+            }
+            Step::Wind => {
+                let (rest, (consumed, wind)) = consumed(parse_wind)(s)?;
+                self.wind = Some(wind);
+                self.spans.wind = self.span(consumed);
+                rest
+            }
+            Step::Visibility => {
+                let (rest, (consumed, visibility)) = consumed(parse_visibility)(s)?;
+                self.visibility = Some(visibility);
+                self.spans.visibility = self.span(consumed);
+                rest
+            }
+            Step::Rvr => {
+                let (rest, rvrs) = many0(consumed(preceded(multispace0, parse_rvr)))(s)?;
+                for (consumed, rvr) in rvrs {
+                    self.spans.runway_visual_range.extend(self.span(consumed));
+                    self.runway_visual_range.push(rvr);
+                }
+                rest
+            }
+            Step::Weather => {
+                let (rest, weather) = many0(consumed(parse_present_weather))(s)?;
+                for (consumed, weather) in weather {
+                    self.spans.weather.extend(self.span(consumed));
+                    self.weather.push(weather);
+                }
+                rest
+            }
+            Step::Clouds => {
+                let (rest, clouds) = many0(consumed(parse_cloud_layer))(s)?;
+                for (consumed, layer) in clouds {
+                    self.spans.clouds.extend(self.span(consumed));
+                    self.clouds.push(layer);
+                }
+                rest
+            }
+            Step::Temperature => {
+                let (rest, temperature) = opt(consumed(parse_temperature))(s)?;
+                if let Some((consumed, temperature)) = temperature {
+                    self.temperature = Some(temperature);
+                    self.spans.temperature = self.span(consumed);
+                }
+                rest
+            }
+            Step::Pressure => {
+                let (rest, pressure) = many0(consumed(parse_pressure))(s)?;
+                for (consumed, pressure) in pressure {
+                    self.spans.pressure.extend(self.span(consumed));
+                    self.pressure.push(pressure);
+                }
+                rest
+            }
+            Step::Supplementary => {
+                let (rest, (recent_weather, wind_shear, sea_state)) = tuple((
+                    many0(consumed(parse_recent_weather)),
+                    many0(consumed(parse_wind_shear)),
+                    opt(consumed(parse_sea_state)),
+                ))(s)?;
+                for (consumed, recent_weather) in recent_weather {
+                    self.spans.recent_weather.extend(self.span(consumed));
+                    self.supplementary.recent_weather.push(recent_weather);
+                }
+                for (consumed, wind_shear) in wind_shear {
+                    self.spans.wind_shear.extend(self.span(consumed));
+                    self.supplementary.wind_shear.push(wind_shear);
+                }
+                if let Some((consumed, sea_state)) = sea_state {
+                    self.supplementary.sea_state = Some(sea_state);
+                    self.spans.sea_state = self.span(consumed);
+                }
+                rest
+            }
+            Step::RunwayState | Step::RunwayStateAfterTrend => {
+                let (rest, runway_state) = many0(consumed(parse_runway_state))(s)?;
+                for (consumed, runway_state) in runway_state {
+                    self.spans.runway_state.extend(self.span(consumed));
+                    self.runway_state.push(runway_state);
+                }
+                rest
+            }
+            Step::Colour => {
+                let (rest, colour) = opt(consumed(parse_colour_code))(s)?;
+                if let Some((consumed, colour)) = colour {
+                    self.colour = Some(colour);
+                    self.spans.colour = self.span(consumed);
+                }
+                rest
+            }
+            Step::Trend => {
+                if let Ok((rest, (consumed, trend))) = consumed(parse_nosig)(s) {
+                    self.trend = Some(trend);
+                    self.spans.trend.extend(self.span(consumed));
+                    return Ok((rest, ()));
+                }
+                let (rest, changes) = many0(consumed(parse_trend_change))(s)?;
+                if !changes.is_empty() {
+                    let mut trend = vec![];
+                    for (consumed, change) in changes {
+                        self.spans.trend.extend(self.span(consumed));
+                        trend.push(change);
+                    }
+                    self.trend = Some(Trend::Changes(trend));
+                }
+                rest
+            }
+        };
+        Ok((rest, ()))
+    }
+
+    /// Decodes the `RMK` section, which never fails: unknown remarks are kept
+    /// as raw tokens.
+    fn remarks(&mut self, remarks: Option<&'a str>) -> Remarks {
+        let mut decoded = Remarks::default();
+        let groups = remarks.and_then(|s| many0(consumed(parse_remark))(s).ok());
+        for (consumed, remark) in groups.map(|(_, groups)| groups).unwrap_or_default() {
+            self.spans.remarks.extend(self.span(consumed));
+            decoded.push(remark, consumed);
+        }
+        decoded
+    }
+
+    /// Builds the report, with placeholders for mandatory elements that were
+    /// never decoded. That only happens in lenient mode.
+    fn into_metar(self, remarks: Remarks) -> (Metar, MetarSpans) {
+        let metar = Metar {
+            kind: self.kind,
+            corrected: self.corrected,
+            report_type: self.report_type.unwrap_or(ReportType::Manual),
+            station: self.station.unwrap_or_default(),
+            time: self.time.unwrap_or(NO_TIME),
+            wind: self.wind.unwrap_or(NO_WIND),
+            visibility: self.visibility.unwrap_or(Visibility::Missing),
+            runway_visual_range: self.runway_visual_range,
+            weather: self.weather,
+            clouds: self.clouds,
+            temperature: self.temperature,
+            pressure: self.pressure,
+            supplementary: self.supplementary,
+            runway_state: self.runway_state,
+            colour: self.colour,
+            trend: self.trend,
+            remarks,
+        };
+        (metar, self.spans)
+    }
+}
+
+#[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum ReportKind {
+    /// Routine report.
+    Metar,
+    /// Special report, issued when conditions change between routine ones.
+    Speci,
+}
+
+impl Code for ReportKind {
+    const ELEMENT: MetarElement = MetarElement::Header;
+    const EXPECTED: &'static str = "METAR or SPECI";
+
+    fn from_code(s: &str) -> Option<Self> {
+        if s.eq_ignore_ascii_case("METAR") {
+            Some(Self::Metar)
+        } else if s.eq_ignore_ascii_case("SPECI") {
+            Some(Self::Speci)
+        } else {
+            None
+        }
+    }
+}
+
+impl FromStr for ReportKind {
+    type Err = MetarError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        decode(s)
+    }
+}
+
+#[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum ReportType {
     Manual,
     Auto,
     Nil,
 }
 
 impl FromStr for ReportType {
-    type Err = anyhow::Error;
+    type Err = MetarError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         match s {
@@ -56,48 +700,290 @@ impl FromStr for ReportType {
     }
 }
 
-#[derive(Debug, PartialEq)]
-struct Time {
-    day: u8,
-    hour: u8,
-    minute: u8,
+impl fmt::Display for ReportKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ReportKind::Metar => f.write_str("METAR"),
+            ReportKind::Speci => f.write_str("SPECI"),
+        }
+    }
+}
+
+/// A manual report has no keyword and writes nothing.
+impl fmt::Display for ReportType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ReportType::Manual => Ok(()),
+            ReportType::Auto => f.write_str("AUTO"),
+            ReportType::Nil => f.write_str("NIL"),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Time {
+    pub day: u8,
+    pub hour: u8,
+    pub minute: u8,
 }
 
 impl Time {
-    fn from_vec(v: Vec<&str>) -> anyhow::Result<Time> {
-        assert!(v.len() == 3);
-        let day = parse_with_bounds(1, 31, v[0])?;
-        let hour = parse_with_bounds(0, 23, v[1])?;
-        let minute = parse_with_bounds(0, 59, v[2])?;
-        Ok(Time { day, hour, minute })
+    /// Decodes the `DD`, `HH` and `MM` digit pairs of a `DDHHMMZ` group.
+    fn from_digits(day: &str, hour: &str, minute: &str) -> Option<Time> {
+        Some(Time {
+            day: parse_with_bounds(1, 31, day)?,
+            hour: parse_with_bounds(0, 23, hour)?,
+            minute: parse_with_bounds(0, 59, minute)?,
+        })
     }
 }
 
-#[derive(Debug, PartialEq)]
+impl fmt::Display for Time {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:02}{:02}{:02}Z", self.day, self.hour, self.minute)
+    }
+}
+
+#[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Metar {
-    report_type: ReportType,
-    station: String,
-    time: Time,
-    wind: Wind,
-    visibility: Visibility,
-    runway_visual_range: Vec<RunwayVisualRange>,
+    /// The `METAR` or `SPECI` keyword, when the report starts with one.
+    pub kind: Option<ReportKind>,
+    /// `COR`: the report corrects a previously issued one.
+    pub corrected: bool,
+    pub report_type: ReportType,
+    pub station: String,
+    pub time: Time,
+    pub wind: Wind,
+    pub visibility: Visibility,
+    pub runway_visual_range: Vec<RunwayVisualRange>,
+    pub weather: Vec<PresentWeather>,
+    pub clouds: Vec<CloudLayer>,
+    pub temperature: Option<Temperature>,
+    pub pressure: Vec<Pressure>,
+    pub supplementary: Supplementary,
+    pub runway_state: Vec<RunwayState>,
+    pub colour: Option<ColourCode>,
+    pub trend: Option<Trend>,
+    pub remarks: Remarks,
+}
+
+/// Byte range of an element in the report string.
+pub type Span = Range<usize>;
+
+/// Where each element of a [`Metar`] came from in the report string. List
+/// elements have one span per group, in the same order as in the `Metar`.
+/// Optional elements that are absent have no span.
+#[derive(Debug, PartialEq, Default, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct MetarSpans {
+    pub kind: Option<Span>,
+    /// The `COR` group, wherever it appeared in the header.
+    pub corrected: Option<Span>,
+    pub report_type: Option<Span>,
+    pub station: Option<Span>,
+    pub time: Option<Span>,
+    /// The wind group, including any variable sector such as `200V240`.
+    pub wind: Option<Span>,
+    /// Prevailing visibility, including any directional minima.
+    pub visibility: Option<Span>,
+    pub runway_visual_range: Vec<Span>,
+    pub weather: Vec<Span>,
+    pub clouds: Vec<Span>,
+    pub temperature: Option<Span>,
+    pub pressure: Vec<Span>,
+    pub recent_weather: Vec<Span>,
+    pub wind_shear: Vec<Span>,
+    pub sea_state: Option<Span>,
+    pub runway_state: Vec<Span>,
+    pub colour: Option<Span>,
+    /// `NOSIG`, or one span per `BECMG` or `TEMPO` block.
+    pub trend: Vec<Span>,
+    /// One span per remark, in report order. A remark such as
+    /// `PK WND 28045/1955` covers several groups.
+    pub remarks: Vec<Span>,
+}
+
+/// Result of [`Metar::parse_lenient`].
+#[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct LenientMetar<'a> {
+    /// Every element that could be decoded; see [`Metar::parse_lenient`] for
+    /// how undecodable mandatory elements are filled in.
+    pub metar: Metar,
+    pub spans: MetarSpans,
+    /// One entry per skipped group and per mandatory element not found.
+    pub diagnostics: Vec<MetarError>,
+    /// Body groups that were skipped, in report order. Unknown remarks are
+    /// kept in `metar.remarks.unparsed` as usual.
+    #[cfg_attr(feature = "serde", serde(borrow))]
+    pub unparsed: Vec<&'a str>,
+}
+
+impl FromStr for Metar {
+    type Err = MetarError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Metar::parse(s)
+    }
+}
+
+/// Writes the report back as canonical text, with single spaces between
+/// groups and without the trailing `=`. Decoded values are written the way
+/// [`Metar::parse`] reads them, so parsing the output gives back an equal
+/// report, but the original spelling is not kept: the remarks are
+/// reordered, for instance, and runway state groups sent after the trend
+/// are written before it.
+impl fmt::Display for Metar {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut w = GroupWriter::new(f);
+        w.groups(&self.kind)?;
+        w.groups(self.corrected.then_some("COR"))?;
+        w.group(&self.station)?;
+        w.group(&self.time)?;
+        w.groups(Some(&self.report_type).filter(|t| **t != ReportType::Manual))?;
+        w.group(&self.wind)?;
+        w.group(&self.visibility)?;
+        w.groups(&self.runway_visual_range)?;
+        w.groups(&self.weather)?;
+        w.groups(&self.clouds)?;
+        w.groups(&self.temperature)?;
+        w.groups(&self.pressure)?;
+        w.section(&self.supplementary)?;
+        w.groups(&self.runway_state)?;
+        w.groups(&self.colour)?;
+        w.groups(&self.trend)?;
+        if self.remarks != Remarks::default() {
+            w.group("RMK")?;
+            w.section(&self.remarks)?;
+        }
+        Ok(())
+    }
 }
 
 impl Metar {
-    pub fn parse(s: &str) -> Result<Metar, nom::Err<nom::error::Error<&str>>> {
-        let (_, (station, (time, _), report_type, wind, visibility)) =
-            tuple((take4, time, report_type, parse_wind, parse_visibility))(
-                s.trim_start_matches("Metar").trim(),
-            )?;
-
-        Ok(Metar {
-            report_type,
-            station: station.to_owned(),
-            time,
-            wind,
-            visibility,
-            runway_visual_range: vec![],
-        })
+    pub fn parse(s: &str) -> Result<Metar, MetarError> {
+        Metar::parse_with_spans(s).map(|(metar, _)| metar)
+    }
+
+    /// Like [`Metar::parse`], also returning where each element sits in `s`.
+    pub fn parse_with_spans(s: &str) -> Result<(Metar, MetarSpans), MetarError> {
+        let mut elements = Elements::new(s);
+        let remarks = parse_steps(s, |step, s| elements.apply(step, s))?;
+        let remarks = elements.remarks(remarks);
+        Ok(elements.into_metar(remarks))
+    }
+
+    /// Parses as much of the report as possible. Groups that no element
+    /// accepts are skipped and reported instead of failing the whole parse,
+    /// and the elements after them are still decoded. A mandatory element
+    /// that cannot be found is reported and left as a placeholder: an empty
+    /// station, time `000000Z`, or missing wind or visibility.
+    pub fn parse_lenient(s: &str) -> LenientMetar<'_> {
+        let input = s;
+        let s = s.trim().trim_end_matches('=').trim_end();
+        let (body, remarks) = split_remarks(s);
+        let mut cursor = Cursor::new(input, body);
+
+        let mut elements = Elements::new(input);
+        let mut diagnostics: Vec<MetarError> = vec![];
+        let mut unparsed = vec![];
+        let mut matched = [false; Step::ALL.len()];
+        let mut next = 0;
+        while !cursor.is_empty() {
+            let found = (next..Step::ALL.len())
+                .find(|&i| cursor.advance(|s| elements.apply(Step::ALL[i], s)));
+            match found {
+                Some(i) => {
+                    matched[i] = true;
+                    // Retry a repeated element after a skipped group, e.g.
+                    // the second RVR in `R24/1200 XXX R06/0800`.
+                    next = if Step::ALL[i].is_repeated() { i } else { i + 1 };
+                }
+                None => {
+                    let pending = Step::ALL[next..]
+                        .iter()
+                        .find(|step| step.is_mandatory() && !matched[step.index()]);
+                    let error = match pending {
+                        Some(step) => cursor.error(step.element(), step.expected()),
+                        None => cursor.unexpected(),
+                    };
+                    diagnostics.push(error);
+                    unparsed.push(cursor.skip());
+                }
+            }
+        }
+
+        for step in Step::ALL {
+            let reported = diagnostics.iter().any(|d| d.element() == step.element());
+            if step.is_mandatory() && !matched[step.index()] && !reported {
+                diagnostics.push(cursor.error(step.element(), step.expected()));
+            }
+        }
+
+        let remarks = elements.remarks(remarks);
+        let (metar, spans) = elements.into_metar(remarks);
+        LenientMetar {
+            metar,
+            spans,
+            diagnostics,
+            unparsed,
+        }
+    }
+
+    /// Altimeter setting in hPa, taken from the first pressure group that
+    /// carries a value, whichever unit it was reported in.
+    pub fn pressure_hpa(&self) -> Option<f64> {
+        self.pressure.iter().find_map(Pressure::hpa)
+    }
+
+    /// Whether the element measured by `sensor` is in the report, and when
+    /// it is not, whether the remarks flag that sensor as out of service.
+    pub fn element_status(&self, sensor: SensorKind) -> ElementStatus {
+        let reported = match sensor {
+            SensorKind::Rvr => !self.runway_visual_range.is_empty(),
+            SensorKind::PresentWeather => self.weather.iter().any(|w| !w.is_not_observed()),
+            SensorKind::Precipitation => self.weather.iter().any(|w| {
+                w.phenomena
+                    .iter()
+                    .any(|p| p.category() == PhenomenonCategory::Precipitation)
+            }),
+            SensorKind::FreezingRain => self.weather.iter().any(|w| {
+                w.descriptor == Some(WeatherDescriptor::Freezing)
+                    && w.phenomena.contains(&WeatherPhenomenon::Rain)
+            }),
+            SensorKind::Lightning => {
+                self.weather
+                    .iter()
+                    .any(|w| w.descriptor == Some(WeatherDescriptor::Thunderstorm))
+                    || !self.remarks.lightning.is_empty()
+            }
+            // Secondary-location sensors have no decoded element of their own.
+            SensorKind::Visibility | SensorKind::Ceiling => false,
+        };
+
+        if reported {
+            ElementStatus::Reported
+        } else if self.remarks.sensor_status.is_out(sensor) {
+            ElementStatus::SensorOutage
+        } else {
+            ElementStatus::NotReported
+        }
+    }
+
+    /// Colour state expected from the reported visibility and cloud base.
+    pub fn expected_colour_state(&self) -> Option<ColourState> {
+        ColourState::derive(&self.visibility, &self.clouds)
+    }
+
+    /// Whether the reported colour state disagrees with the one derived from
+    /// visibility and cloud base. Reports without a colour state never do.
+    pub fn colour_state_mismatch(&self) -> bool {
+        match (&self.colour, self.expected_colour_state()) {
+            (Some(reported), Some(expected)) => reported.state != expected,
+            _ => false,
+        }
     }
 }
 
@@ -114,4 +1000,494 @@ mod test {
         assert_eq!(report_type("")?.1, ReportType::Manual);
         Ok(())
     }
+
+    #[test]
+    fn test_parse_header() -> anyhow::Result<()> {
+        let metar = Metar::parse("SPECI KJFK 141651Z COR AUTO 22010KT 10SM A2992=")?;
+        assert_eq!(metar.kind, Some(ReportKind::Speci));
+        assert!(metar.corrected);
+        assert_eq!(metar.report_type, ReportType::Auto);
+        assert_eq!(metar.station, "KJFK");
+
+        let metar = Metar::parse("metar COR LFPG 141630Z 22010KT 9999 Q1013 NOSIG=")?;
+        assert_eq!(metar.kind, Some(ReportKind::Metar));
+        assert!(metar.corrected);
+        assert_eq!(metar.report_type, ReportType::Manual);
+        assert_eq!(metar.trend, Some(Trend::NoSignificantChange));
+
+        let metar = Metar::parse("LFPG 141630Z AUTO COR 22010KT 9999 Q1013")?;
+        assert_eq!(metar.kind, None);
+        assert!(metar.corrected);
+        assert_eq!(metar.report_type, ReportType::Auto);
+
+        let metar = Metar::parse("KJFK 141651Z 22010KT 10SM A2992 RMK AO2 $=")?;
+        assert!(!metar.corrected);
+        assert!(metar.remarks.sensor_status.maintenance_required);
+        assert!(metar.remarks.unparsed.is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_visibility() -> anyhow::Result<()> {
+        let metar = Metar::parse("EDDF 141650Z AUTO 24008KT 4000 1500NE 2000SW BR BKN008")?;
+        match &metar.visibility {
+            Visibility::Prevailing { directional, .. } => assert_eq!(directional.len(), 2),
+            other => panic!("unexpected visibility {:?}", other),
+        }
+        assert_eq!(metar.weather.len(), 1);
+
+        let metar = Metar::parse("EDDF 141650Z AUTO 24008KT //// OVC004 M01/M03 Q1015")?;
+        assert_eq!(metar.visibility, Visibility::Missing);
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_missing_elements() -> anyhow::Result<()> {
+        let metar = Metar::parse(
+            "METAR LFPO 141630Z AUTO /////KT //// R24///// // //////CB ///// Q//// RE// RMK PWINO=",
+        )?;
+        assert_eq!(metar.wind.direction, wind::WindDirection::Missing);
+        assert_eq!(metar.wind.speed, wind::WindSpeed::Missing);
+        assert_eq!(metar.visibility, Visibility::Missing);
+        assert_eq!(metar.runway_visual_range[0].visibility, None);
+        assert!(metar.weather[0].is_not_observed());
+        assert_eq!(
+            metar.clouds,
+            vec![CloudLayer::Layer {
+                cover: None,
+                height: None,
+                cloud_type: Some(clouds::CloudType::Cumulonimbus)
+            }]
+        );
+        assert_eq!(
+            metar.temperature,
+            Some(Temperature {
+                air: None,
+                dew_point: None
+            })
+        );
+        assert_eq!(metar.pressure_hpa(), None);
+        assert_eq!(metar.pressure.len(), 1);
+        assert_eq!(
+            metar.supplementary.recent_weather[0].phenomena,
+            vec![WeatherPhenomenon::NotObserved]
+        );
+        assert_eq!(
+            metar.element_status(SensorKind::PresentWeather),
+            ElementStatus::SensorOutage
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        let error = Metar::parse("LFPG 141630Z 220X0KT 9999 Q1013").unwrap_err();
+        assert_eq!(
+            error,
+            MetarError::Invalid {
+                element: MetarElement::Wind,
+                offset: 13,
+                token: "220X0KT".to_owned(),
+                expected: "wind group such as 22010KT".into(),
+            }
+        );
+
+        let error = Metar::parse("LFPG 141630Z 22010KT 9999 R24/ABCD Q1013").unwrap_err();
+        assert_eq!(error.element(), MetarElement::Rvr);
+        assert_eq!(error.offset(), 26);
+        assert_eq!(error.token(), "R24/ABCD");
+
+        let error = Metar::parse("METAR LFPG 321630Z 22010KT 9999").unwrap_err();
+        assert_eq!(error.element(), MetarElement::Time);
+        assert_eq!(error.offset(), 11);
+
+        let error = Metar::parse("  LFPG 141630Z").unwrap_err();
+        assert_eq!(
+            error,
+            MetarError::Missing {
+                element: MetarElement::Wind,
+                offset: 14,
+                expected: "wind group such as 22010KT".into(),
+            }
+        );
+
+        assert_eq!(
+            "".parse::<Metar>().unwrap_err().element(),
+            MetarElement::Station
+        );
+        assert_eq!(
+            Metar::parse("LFPGX 141630Z 22010KT 9999")
+                .unwrap_err()
+                .element(),
+            MetarElement::Station
+        );
+    }
+
+    #[test]
+    fn test_time_from_digits() {
+        assert_eq!(
+            Time::from_digits("14", "16", "30"),
+            Some(Time {
+                day: 14,
+                hour: 16,
+                minute: 30
+            })
+        );
+        assert_eq!(Time::from_digits("32", "16", "30"), None);
+    }
+
+    #[test]
+    fn test_parse_lenient() {
+        let sample = "LFPG 141630Z 22010KT 9999 R24/ABCD R06/0800 XX1 -RA BKN020 15/10 Q1013 NOSIG";
+        let parsed = Metar::parse_lenient(sample);
+        assert_eq!(parsed.unparsed, vec!["R24/ABCD", "XX1"]);
+        assert_eq!(parsed.diagnostics.len(), 2);
+        assert_eq!(parsed.diagnostics[0].element(), MetarElement::Rvr);
+        assert_eq!(parsed.diagnostics[0].offset(), 26);
+        assert_eq!(parsed.diagnostics[1].element(), MetarElement::Unknown);
+        let metar = parsed.metar;
+        assert_eq!(metar.station, "LFPG");
+        assert_eq!(metar.runway_visual_range.len(), 1);
+        assert_eq!(metar.weather.len(), 1);
+        assert_eq!(metar.clouds.len(), 1);
+        assert_eq!(metar.pressure_hpa(), Some(1013.0));
+        assert_eq!(metar.trend, Some(Trend::NoSignificantChange));
+        assert!(Metar::parse(sample).is_err());
+
+        let parsed = Metar::parse_lenient("LFPG 141630Z 220X0KT 9999 BKN020 RMK AO2");
+        assert_eq!(parsed.unparsed, vec!["220X0KT"]);
+        assert_eq!(parsed.diagnostics.len(), 1);
+        assert_eq!(parsed.diagnostics[0].element(), MetarElement::Wind);
+        assert_eq!(parsed.metar.wind.speed, wind::WindSpeed::Missing);
+        assert_eq!(parsed.metar.clouds.len(), 1);
+        assert_eq!(
+            parsed.metar.remarks.station_type,
+            Some(remarks::StationType::Ao2)
+        );
+
+        let parsed = Metar::parse_lenient("LFPG 141630Z 22010KT");
+        assert!(parsed.unparsed.is_empty());
+        assert_eq!(
+            parsed.diagnostics,
+            vec![MetarError::Missing {
+                element: MetarElement::Visibility,
+                offset: 20,
+                expected: "visibility such as 9999, 1/2SM or CAVOK".into(),
+            }]
+        );
+        assert_eq!(parsed.metar.visibility, Visibility::Missing);
+
+        let sample = "METAR LICJ 141600Z 12012KT 3000 -SHRA BKN022 M04/M07 Q1020 NOSIG=";
+        let parsed = Metar::parse_lenient(sample);
+        assert!(parsed.diagnostics.is_empty());
+        assert_eq!(Some(parsed.metar), Metar::parse(sample).ok());
+    }
+
+    #[test]
+    fn test_parse_with_spans() -> anyhow::Result<()> {
+        let sample = "METAR LFPG 141630Z AUTO 22010KT 180V250 9999 R24/1200 R06/P2000 -RA FEW020 BKN040 15/10 Q1013 NOSIG RMK AO2=";
+        let (metar, spans) = Metar::parse_with_spans(sample)?;
+        let text = |span: &Option<Span>| span.clone().map(|span| &sample[span]);
+        assert_eq!(text(&spans.kind), Some("METAR"));
+        assert_eq!(text(&spans.station), Some("LFPG"));
+        assert_eq!(text(&spans.time), Some("141630Z"));
+        assert_eq!(text(&spans.report_type), Some("AUTO"));
+        assert_eq!(text(&spans.corrected), None);
+        assert_eq!(text(&spans.wind), Some("22010KT 180V250"));
+        assert_eq!(text(&spans.visibility), Some("9999"));
+        let rvrs: Vec<_> = spans
+            .runway_visual_range
+            .iter()
+            .map(|span| &sample[span.clone()])
+            .collect();
+        assert_eq!(rvrs, vec!["R24/1200", "R06/P2000"]);
+        assert_eq!(
+            spans.runway_visual_range.len(),
+            metar.runway_visual_range.len()
+        );
+        assert_eq!(&sample[spans.weather[0].clone()], "-RA");
+        assert_eq!(&sample[spans.clouds[1].clone()], "BKN040");
+        assert_eq!(text(&spans.temperature), Some("15/10"));
+        assert_eq!(&sample[spans.pressure[0].clone()], "Q1013");
+        assert_eq!(spans.trend.len(), 1);
+        assert_eq!(&sample[spans.trend[0].clone()], "NOSIG");
+        assert_eq!(&sample[spans.remarks[0].clone()], "AO2");
+        assert_eq!(text(&spans.colour), None);
+        assert_eq!(metar, Metar::parse(sample)?);
+
+        let sample = "EDDF 141650Z 24008KT 9999 BKN008 01/M03 Q1015 RESHSN RETS WS R25L W05/S3 \
+            BECMG FM1700 9999 NSW TEMPO 3000 SN RMK PK WND 28045/1955 SLP132 ESTMD";
+        let (metar, spans) = Metar::parse_with_spans(sample)?;
+        let text = |span: &Option<Span>| span.clone().map(|span| &sample[span]);
+        let texts = |spans: &[Span]| -> Vec<&str> {
+            spans.iter().map(|span| &sample[span.clone()]).collect()
+        };
+        assert_eq!(texts(&spans.recent_weather), vec!["RESHSN", "RETS"]);
+        assert_eq!(texts(&spans.wind_shear), vec!["WS R25L"]);
+        assert_eq!(text(&spans.sea_state), Some("W05/S3"));
+        assert_eq!(
+            texts(&spans.trend),
+            vec!["BECMG FM1700 9999 NSW", "TEMPO 3000 SN"]
+        );
+        assert_eq!(
+            texts(&spans.remarks),
+            vec!["PK WND 28045/1955", "SLP132", "ESTMD"]
+        );
+        assert_eq!(
+            spans.recent_weather.len(),
+            metar.supplementary.recent_weather.len()
+        );
+
+        let parsed = Metar::parse_lenient("LFPG 141630Z 22010KT 9999 XX1 BKN020");
+        assert_eq!(parsed.spans.clouds, vec![30..36]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_rvr() -> anyhow::Result<()> {
+        let metar =
+            Metar::parse("KORD 141651Z 27015KT 1/2SM R10L/1000V1600FT R28R///// FG OVC002")?;
+        assert_eq!(metar.runway_visual_range.len(), 2);
+        let rvr = &metar.runway_visual_range[0];
+        assert_eq!(
+            rvr.visibility.as_ref().map(|d| d.unit),
+            Some(rvr::RvrUnit::Feet)
+        );
+        assert_eq!(
+            rvr.variable_visibility.as_ref().map(|d| d.value),
+            Some(1600)
+        );
+        assert_eq!(metar.runway_visual_range[1].visibility, None);
+        assert_eq!(metar.weather.len(), 1);
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_present_weather() -> anyhow::Result<()> {
+        let metar = Metar::parse("LICJ 141600Z 12012KT 3000 R04/P1500N -SHRA BR BKN022")?;
+        assert_eq!(metar.runway_visual_range.len(), 1);
+        assert_eq!(metar.weather.len(), 2);
+        assert_eq!(
+            metar.weather[0].descriptor,
+            Some(weather::WeatherDescriptor::Showers)
+        );
+        assert_eq!(metar.clouds.len(), 1);
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_temperature() -> anyhow::Result<()> {
+        let metar = Metar::parse("LICJ 141600Z 12012KT 3000 +SN BKN022 M04/M07 Q1020")?;
+        assert_eq!(
+            metar.temperature,
+            Some(Temperature {
+                air: Some((-4).into()),
+                dew_point: Some((-7).into())
+            })
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_pressure() -> anyhow::Result<()> {
+        let metar = Metar::parse("KJFK 141600Z 12012KT 10SM FEW250 22/15 A2992 Q1013")?;
+        assert_eq!(metar.pressure.len(), 2);
+        assert_eq!(metar.pressure_hpa().map(f64::round), Some(1013.0));
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_supplementary() -> anyhow::Result<()> {
+        let metar = Metar::parse("EDDF 141600Z 27015KT 9999 FEW030 15/08 Q1012 RETSRA WS R25L")?;
+        assert_eq!(metar.supplementary.recent_weather.len(), 1);
+        assert_eq!(metar.supplementary.wind_shear.len(), 1);
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_runway_state() -> anyhow::Result<()> {
+        let metar = Metar::parse(
+            "ESSA 141600Z 36010KT 9999 -SN OVC012 M04/M07 Q1020 R01L/451293 8849//91",
+        )?;
+        assert_eq!(metar.runway_state.len(), 2);
+        assert_eq!(
+            metar.runway_state[1].runway,
+            runway_state::RunwayStateDesignator::AllRunways
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_colour_state() -> anyhow::Result<()> {
+        let metar = Metar::parse("EGXC 141620Z 24015KT 9999 SCT030 12/05 Q1008 BLU NOSIG")?;
+        assert_eq!(
+            metar.colour.as_ref().map(|c| c.state),
+            Some(ColourState::Blue)
+        );
+        assert!(!metar.colour_state_mismatch());
+        assert_eq!(metar.trend, Some(Trend::NoSignificantChange));
+
+        let metar = Metar::parse("EGXC 141620Z 24015KT 9999 BKN008 12/05 Q1008 BLACKBLU")?;
+        assert_eq!(metar.expected_colour_state(), Some(ColourState::Green));
+        assert!(metar.colour_state_mismatch());
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_remarks() -> anyhow::Result<()> {
+        let metar = Metar::parse(
+            "KJFK 141651Z 22010KT 10SM FEW250 08/03 A2992 RMK AO2 SLP132 T00830033 ESTMD",
+        )?;
+        assert_eq!(metar.pressure.len(), 1);
+        assert_eq!(metar.remarks.station_type, Some(remarks::StationType::Ao2));
+        assert_eq!(metar.remarks.unparsed, vec!["ESTMD"]);
+        let metar = Metar::parse("EGLL 141650Z 22010KT 9999 FEW040 15/10 Q1013\tRMK AO2")?;
+        assert_eq!(metar.remarks.station_type, Some(remarks::StationType::Ao2));
+        Ok(())
+    }
+
+    #[test]
+    fn test_element_status() -> anyhow::Result<()> {
+        let metar =
+            Metar::parse("KBOS 141654Z 22010KT 10SM -RA OVC040 08/03 A2992 RMK AO2 RVRNO TSNO $")?;
+        assert_eq!(
+            metar.element_status(SensorKind::Rvr),
+            ElementStatus::SensorOutage
+        );
+        assert_eq!(
+            metar.element_status(SensorKind::Precipitation),
+            ElementStatus::Reported
+        );
+        assert_eq!(
+            metar.element_status(SensorKind::FreezingRain),
+            ElementStatus::NotReported
+        );
+        assert!(metar.remarks.sensor_status.maintenance_required);
+        Ok(())
+    }
+
+    #[test]
+    fn test_remarks_only_report() {
+        assert_eq!(
+            Metar::parse("RMK AO2"),
+            Err(MetarError::Missing {
+                element: MetarElement::Station,
+                offset: 0,
+                expected: "four-character ICAO identifier".into(),
+            })
+        );
+        assert_eq!(
+            Metar::parse_lenient("RMK AO2").metar.remarks.station_type,
+            Some(remarks::StationType::Ao2)
+        );
+    }
+
+    #[test]
+    fn test_parse_trend() -> anyhow::Result<()> {
+        let metar = Metar::parse("Metar LICJ 141600Z 120120G50KT 090V150 CAVOK R04/P1500N R22/P1500U +SN BKN022 OVC050 M04/M07 Q1020 NOSIG 8849//91=")?;
+        assert_eq!(metar.trend, Some(Trend::NoSignificantChange));
+        assert_eq!(metar.runway_state.len(), 1);
+
+        let metar = Metar::parse(
+            "EGLL 141620Z 24015KT 9999 SCT030 12/05 Q1008 TEMPO 24025G35KT 4000 SHRA",
+        )?;
+        let Some(Trend::Changes(changes)) = metar.trend else {
+            panic!("Expected trend changes")
+        };
+        assert_eq!(changes[0].indicator, trend::ChangeIndicator::Temporary);
+        Ok(())
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde_round_trip() -> anyhow::Result<()> {
+        for sample in [
+            "Metar LICJ 141600Z 120120G50KT 090V150 CAVOK R04/P1500N R22/P1500U +SN BKN022 OVC050 M04/M07 Q1020 NOSIG 8849//91=",
+            "SPECI KJFK 141651Z COR AUTO 22010KT 1 1/2SM R04R/1000V1600FT -RA BR FEW008 BKN020 12/11 A2992 RMK AO2 SLP132 T01220111 $",
+            "EGLL 141620Z 24015KT 9999 SCT030 12/05 Q1008 TEMPO 24025G35KT 4000 SHRA",
+        ] {
+            let metar = Metar::parse(sample)?;
+            let json = serde_json::to_string(&metar)?;
+            assert_eq!(serde_json::from_str::<Metar>(&json)?, metar);
+        }
+        Ok(())
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde_round_trip_lenient() -> anyhow::Result<()> {
+        let sample = "LFPG 141630Z 22010KT 9999 R24/ABCD R06/0800 XX1 -RA BKN020 15/10 Q1013 NOSIG";
+        let parsed = Metar::parse_lenient(sample);
+        let json = serde_json::to_string(&parsed)?;
+        assert_eq!(serde_json::from_str::<LenientMetar>(&json)?, parsed);
+
+        let error = Metar::parse("LFPG 141630Z 220X0KT 9999").unwrap_err();
+        let json = serde_json::to_string(&error)?;
+        assert_eq!(serde_json::from_str::<MetarError>(&json)?, error);
+        Ok(())
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde_representation() -> anyhow::Result<()> {
+        let metar = Metar::parse("LFPG 141630Z 22010KT CAVOK 15/10 Q1013")?;
+        assert_eq!(
+            serde_json::to_value(&metar.wind)?,
+            serde_json::json!({
+                "direction": {"type": "direct", "value": 220},
+                "speed": {"type": "exact", "value": 10},
+                "gust_speed": null,
+                "unit": "kt",
+                "variable_direction": null,
+            })
+        );
+        assert_eq!(
+            serde_json::to_value(&metar.visibility)?,
+            serde_json::json!({"type": "cavok"})
+        );
+        assert_eq!(
+            serde_json::to_value(Visibility::from(visibility::VisibilityDistance::Meters(
+                800
+            )))?,
+            serde_json::json!({
+                "type": "prevailing",
+                "value": {
+                    "value": {"distance": {"type": "meters", "value": 800}, "qualifier": null},
+                    "no_directional_variation": false,
+                    "directional": [],
+                }
+            })
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_display_round_trip() -> anyhow::Result<()> {
+        for sample in [
+            "METAR LICJ 141600Z 120120G50KT 090V150 CAVOK R04/P1500N R22/P1500U +SN BKN022 OVC050 M04/M07 Q1020 R88/49//91 NOSIG",
+            "SPECI COR KJFK 141651Z AUTO 22010KT 1 1/2SM R04R/1000V1600FT -RA BR FEW008 BKN020 12/11 A2992 RMK AO2 SLP132 T01220111 $",
+            "EDDF 141650Z AUTO 24008KT 4000 1500NE BR BKN008 M01/M03 Q1015 RESHSN WS R25L BECMG FM1700 9999 NSW",
+            "LFPO 141630Z AUTO /////KT //// R24///// // //////CB ///// Q////",
+            "EGLL 141620Z 24015KT 9999 SCT030 12/05 Q1008 TEMPO 24025G35KT 4000 SHRA",
+            "ENBR 141620Z 00000KT 9999NDV VV002 01/M01 Q0998 W05/H15 R17/CLRD95 BLACKRED",
+        ] {
+            let metar = Metar::parse(sample)?;
+            assert_eq!(metar.to_string(), sample);
+            assert_eq!(Metar::parse(&metar.to_string())?, metar);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_display_normalises_report() -> anyhow::Result<()> {
+        let metar = Metar::parse(
+            "metar LFPG 141630Z COR 22010KT 200V240 9999 R27L/0500 FEW020 15/10 Q1013 NOSIG R27L/290235 RMK SLP132 AO2=",
+        )?;
+        assert_eq!(
+            metar.to_string(),
+            "METAR COR LFPG 141630Z 22010KT 200V240 9999 R27L/0500 FEW020 15/10 Q1013 R27L/290235 NOSIG RMK AO2 SLP132"
+        );
+        assert_eq!(Metar::parse(&metar.to_string())?, metar);
+        Ok(())
+    }
 }
@@ -0,0 +1,194 @@
+use std::fmt;
+use std::str::FromStr;
+
+use nom::{
+    branch::alt,
+    bytes::complete::{tag, take_while_m_n},
+    combinator::{map, map_opt, map_res, value},
+    multi::many0,
+    sequence::{pair, terminated},
+    IResult,
+};
+
+use crate::error::{MetarElement, MetarError};
+use crate::{code, decode, end_of_group, Code};
+
+const HPA_PER_INHG: f64 = 33.8639;
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum PressureUnit {
+    Hpa,
+    InHg,
+}
+
+impl Code for PressureUnit {
+    const ELEMENT: MetarElement = MetarElement::Pressure;
+    const EXPECTED: &'static str = "Q or A";
+
+    fn from_code(s: &str) -> Option<Self> {
+        match s {
+            "Q" => Some(PressureUnit::Hpa),
+            "A" => Some(PressureUnit::InHg),
+            _ => None,
+        }
+    }
+}
+
+impl FromStr for PressureUnit {
+    type Err = MetarError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        decode(s)
+    }
+}
+
+/// Altimeter setting in the unit it was reported in. `value` is `None`
+/// for a missing reading such as `Q////`.
+#[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Pressure {
+    pub value: Option<f64>,
+    pub unit: PressureUnit,
+}
+
+impl Pressure {
+    pub fn hpa(&self) -> Option<f64> {
+        self.convert(PressureUnit::Hpa).value
+    }
+
+    pub fn inhg(&self) -> Option<f64> {
+        self.convert(PressureUnit::InHg).value
+    }
+
+    pub fn convert(&self, unit: PressureUnit) -> Pressure {
+        let value = self.value.map(|v| match (self.unit, unit) {
+            (PressureUnit::Hpa, PressureUnit::InHg) => v / HPA_PER_INHG,
+            (PressureUnit::InHg, PressureUnit::Hpa) => v * HPA_PER_INHG,
+            _ => v,
+        });
+        Pressure { value, unit }
+    }
+}
+
+impl fmt::Display for PressureUnit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PressureUnit::Hpa => f.write_str("Q"),
+            PressureUnit::InHg => f.write_str("A"),
+        }
+    }
+}
+
+/// Writes hectopascals on four digits and inches of mercury in hundredths,
+/// e.g. `Q1013` or `A2992`.
+impl fmt::Display for Pressure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.unit)?;
+        match (self.value, self.unit) {
+            (Some(v), PressureUnit::Hpa) => write!(f, "{:04}", v.round() as u16),
+            (Some(v), PressureUnit::InHg) => write!(f, "{:04}", (v * 100.0).round() as u16),
+            (None, _) => f.write_str("////"),
+        }
+    }
+}
+
+fn parse_value(s: &str) -> IResult<&str, Option<u16>> {
+    alt((
+        map(
+            map_res(
+                take_while_m_n(4, 4, |c: char| c.is_ascii_digit()),
+                str::parse,
+            ),
+            Some,
+        ),
+        value(None, tag("////")),
+    ))(s)
+}
+
+pub fn parse_pressure(s: &str) -> IResult<&str, Pressure> {
+    let s = s.trim_start();
+    let (rest, (unit, raw)) = terminated(
+        pair(
+            map_opt(alt((tag("Q"), tag("A"))), code::<PressureUnit>),
+            parse_value,
+        ),
+        end_of_group,
+    )(s)?;
+
+    let value = raw.map(|v| match unit {
+        PressureUnit::Hpa => v as f64,
+        PressureUnit::InHg => v as f64 / 100.0,
+    });
+    Ok((rest, Pressure { value, unit }))
+}
+
+pub fn parse_pressures(s: &str) -> IResult<&str, Vec<Pressure>> {
+    many0(parse_pressure)(s)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_parse_pressure() -> anyhow::Result<()> {
+        assert_eq!(
+            parse_pressure("Q1020")?.1,
+            Pressure {
+                value: Some(1020.0),
+                unit: PressureUnit::Hpa
+            }
+        );
+        assert_eq!(
+            parse_pressure("A2992")?.1,
+            Pressure {
+                value: Some(29.92),
+                unit: PressureUnit::InHg
+            }
+        );
+        assert_eq!(
+            parse_pressure("Q////")?.1,
+            Pressure {
+                value: None,
+                unit: PressureUnit::Hpa
+            }
+        );
+        assert!(parse_pressure("A29").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_pressures() -> anyhow::Result<()> {
+        let (rest, pressures) = parse_pressures(" A2992 Q1013 NOSIG")?;
+        assert_eq!(rest, " NOSIG");
+        assert_eq!(pressures.len(), 2);
+        assert_eq!(pressures[0].unit, PressureUnit::InHg);
+        assert_eq!(pressures[1].unit, PressureUnit::Hpa);
+        Ok(())
+    }
+
+    #[test]
+    fn test_pressure_conversion() {
+        let inhg = Pressure {
+            value: Some(29.92),
+            unit: PressureUnit::InHg,
+        };
+        assert_eq!(inhg.hpa().map(f64::round), Some(1013.0));
+        let hpa = Pressure {
+            value: Some(1013.0),
+            unit: PressureUnit::Hpa,
+        };
+        assert_eq!(hpa.inhg().map(|v| (v * 100.0).round()), Some(2991.0));
+        assert_eq!(hpa.hpa(), Some(1013.0));
+    }
+
+    #[test]
+    fn test_display_pressure() -> anyhow::Result<()> {
+        for sample in ["Q1020", "Q0998", "A2992", "A3001", "Q////"] {
+            assert_eq!(parse_pressure(sample)?.1.to_string(), sample);
+        }
+        Ok(())
+    }
+}
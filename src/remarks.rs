@@ -0,0 +1,1770 @@
+use std::fmt;
+use std::str::FromStr;
+
+use nom::{
+    branch::alt,
+    bytes::complete::{tag, take_till1, take_while_m_n},
+    character::complete::multispace1,
+    combinator::{consumed, map, map_opt, map_res, opt, peek, recognize, value, verify},
+    multi::{many0, many1, separated_list1},
+    sequence::{pair, preceded, separated_pair, terminated, tuple},
+    IResult,
+};
+
+use crate::error::{MetarElement, MetarError};
+use crate::sensor::{parse_sensor_outage, SensorOutage, SensorStatus};
+use crate::visibility::{
+    fmt_statute_miles, parse_direction, parse_statute_miles, VisibilityDirection,
+};
+use crate::weather::{
+    fmt_weather, parse_descriptor, parse_phenomenon, WeatherDescriptor, WeatherPhenomenon,
+};
+use crate::wind::{Wind, WindDirection, WindSpeed, WindUnit};
+use crate::{code, decode, end_of_group, parse_with_bounds, Code, GroupWriter, WriteGroups};
+
+#[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum StationType {
+    /// `AO1`: automated station without a precipitation discriminator.
+    Ao1,
+    /// `AO2`: automated station with a precipitation discriminator.
+    Ao2,
+}
+
+impl Code for StationType {
+    const ELEMENT: MetarElement = MetarElement::Remarks;
+    const EXPECTED: &'static str = "AO1 or AO2";
+
+    fn from_code(s: &str) -> Option<Self> {
+        match s {
+            "AO1" => Some(StationType::Ao1),
+            "AO2" => Some(StationType::Ao2),
+            _ => None,
+        }
+    }
+}
+
+impl FromStr for StationType {
+    type Err = MetarError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        decode(s)
+    }
+}
+
+#[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(
+    feature = "serde",
+    serde(tag = "type", content = "value", rename_all = "snake_case")
+)]
+pub enum SeaLevelPressure {
+    Hpa(f64),
+    /// `SLPNO`: sea-level pressure not available.
+    NotAvailable,
+}
+
+/// `T` group: air temperature and dew point in tenths of a degree Celsius.
+#[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PreciseTemperature {
+    pub air: f64,
+    pub dew_point: Option<f64>,
+}
+
+/// Value of an additive data group, which may be sent as missing (`////`).
+#[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(
+    feature = "serde",
+    serde(tag = "type", content = "value", rename_all = "snake_case")
+)]
+pub enum AdditiveValue<T> {
+    Value(T),
+    Missing,
+}
+
+/// `4` group: 24-hour maximum and minimum temperature in degrees Celsius.
+#[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TemperatureExtremes {
+    pub max: f64,
+    pub min: f64,
+}
+
+/// `5` group: character and amount of the pressure change over 3 hours.
+#[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PressureTendency {
+    /// WMO code 0-8: 0-3 rising, 4 steady, 5-8 falling.
+    pub character: u8,
+    /// Absolute change in hPa.
+    pub change: f64,
+}
+
+impl PressureTendency {
+    /// Change in hPa, negative when the pressure is falling.
+    pub fn signed_change(&self) -> f64 {
+        match self.character {
+            5..=8 => -self.change,
+            4 => 0.0,
+            _ => self.change,
+        }
+    }
+}
+
+/// Time of an event within the hour of the report. The hour is left out
+/// when it is the same as the report's.
+#[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct RemarkTime {
+    pub hour: Option<u8>,
+    pub minute: u8,
+}
+
+/// `PK WND`: highest instantaneous wind since the last report.
+#[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PeakWind {
+    pub wind: Wind,
+    pub time: RemarkTime,
+}
+
+/// `WSHFT`: wind shift, with `FROPA` when caused by a frontal passage.
+#[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct WindShift {
+    pub time: RemarkTime,
+    pub frontal_passage: bool,
+}
+
+/// `VIS 1/2V2`: variable prevailing visibility, in statute miles.
+#[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct VariableVisibility {
+    pub min: f64,
+    pub max: f64,
+}
+
+/// `VIS NE 2 1/2`: visibility in one sector, in statute miles.
+#[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SectorVisibility {
+    pub direction: VisibilityDirection,
+    pub visibility: f64,
+}
+
+/// `CIG 005V010`: variable ceiling, in hundreds of feet.
+#[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct VariableCeiling {
+    pub min: u16,
+    pub max: u16,
+}
+
+/// Whether a weather event in a `RAB15E30` remark is a beginning or an end.
+#[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum WeatherEventKind {
+    Began,
+    Ended,
+}
+
+#[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct WeatherEventTime {
+    pub kind: WeatherEventKind,
+    pub time: RemarkTime,
+}
+
+/// Begin and end times of one phenomenon, e.g. `RAB15E30` or `TSB0159E30`.
+#[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct WeatherEvent {
+    pub descriptor: Option<WeatherDescriptor>,
+    pub phenomena: Vec<WeatherPhenomenon>,
+    pub times: Vec<WeatherEventTime>,
+}
+
+#[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum LocationDistance {
+    /// `VC`: between 5 and 10 statute miles from the station.
+    Vicinity,
+    /// `DSNT`: more than 10 statute miles from the station.
+    Distant,
+}
+
+#[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(
+    feature = "serde",
+    serde(tag = "type", content = "value", rename_all = "snake_case")
+)]
+pub enum LocationArea {
+    Overhead,
+    AllQuadrants,
+    /// Directions as reported; a range such as `NE-S` lists its endpoints.
+    Directions(Vec<VisibilityDirection>),
+}
+
+#[derive(Debug, PartialEq, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct RemarkLocation {
+    pub distance: Option<LocationDistance>,
+    pub area: Option<LocationArea>,
+}
+
+#[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum PhenomenonKind {
+    Thunderstorm,
+    Cumulonimbus,
+    CumulonimbusMammatus,
+    ToweringCumulus,
+    Virga,
+}
+
+impl Code for PhenomenonKind {
+    const ELEMENT: MetarElement = MetarElement::Remarks;
+    const EXPECTED: &'static str = "TS, CB, CBMAM, TCU or VIRGA";
+
+    fn from_code(s: &str) -> Option<Self> {
+        match s {
+            "TS" => Some(PhenomenonKind::Thunderstorm),
+            "CB" => Some(PhenomenonKind::Cumulonimbus),
+            "CBMAM" => Some(PhenomenonKind::CumulonimbusMammatus),
+            "TCU" => Some(PhenomenonKind::ToweringCumulus),
+            "VIRGA" => Some(PhenomenonKind::Virga),
+            _ => None,
+        }
+    }
+}
+
+impl FromStr for PhenomenonKind {
+    type Err = MetarError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        decode(s)
+    }
+}
+
+/// Location and movement of a thunderstorm or significant cloud, e.g.
+/// `TS OHD MOV NE` or `VIRGA SW`.
+#[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PhenomenonLocation {
+    pub kind: PhenomenonKind,
+    pub location: RemarkLocation,
+    pub movement: Option<VisibilityDirection>,
+}
+
+#[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum LightningFrequency {
+    Occasional,
+    Frequent,
+    Continuous,
+}
+
+impl Code for LightningFrequency {
+    const ELEMENT: MetarElement = MetarElement::Remarks;
+    const EXPECTED: &'static str = "OCNL, FRQ or CONS";
+
+    fn from_code(s: &str) -> Option<Self> {
+        match s {
+            "OCNL" => Some(LightningFrequency::Occasional),
+            "FRQ" => Some(LightningFrequency::Frequent),
+            "CONS" => Some(LightningFrequency::Continuous),
+            _ => None,
+        }
+    }
+}
+
+impl FromStr for LightningFrequency {
+    type Err = MetarError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        decode(s)
+    }
+}
+
+#[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum LightningType {
+    InCloud,
+    CloudToCloud,
+    CloudToGround,
+    CloudToAir,
+}
+
+impl Code for LightningType {
+    const ELEMENT: MetarElement = MetarElement::Remarks;
+    const EXPECTED: &'static str = "IC, CC, CG or CA";
+
+    fn from_code(s: &str) -> Option<Self> {
+        match s {
+            "IC" => Some(LightningType::InCloud),
+            "CC" => Some(LightningType::CloudToCloud),
+            "CG" => Some(LightningType::CloudToGround),
+            "CA" => Some(LightningType::CloudToAir),
+            _ => None,
+        }
+    }
+}
+
+impl FromStr for LightningType {
+    type Err = MetarError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        decode(s)
+    }
+}
+
+/// `OCNL LTGICCG NW`: lightning frequency, types and location.
+#[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Lightning {
+    pub frequency: Option<LightningFrequency>,
+    pub types: Vec<LightningType>,
+    pub location: RemarkLocation,
+}
+
+#[derive(Debug, PartialEq, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Remarks {
+    pub station_type: Option<StationType>,
+    pub sea_level_pressure: Option<SeaLevelPressure>,
+    pub precise_temperature: Option<PreciseTemperature>,
+    /// `P` group, in inches.
+    pub hourly_precipitation: Option<AdditiveValue<f64>>,
+    /// `6` group, in inches, over the last 3 or 6 hours depending on the
+    /// time of the report.
+    pub three_or_six_hour_precipitation: Option<AdditiveValue<f64>>,
+    /// `7` group, in inches.
+    pub twenty_four_hour_precipitation: Option<AdditiveValue<f64>>,
+    /// `4/` group, in inches.
+    pub snow_depth: Option<AdditiveValue<u16>>,
+    /// `1` group, in degrees Celsius.
+    pub six_hour_max_temperature: Option<AdditiveValue<f64>>,
+    /// `2` group, in degrees Celsius.
+    pub six_hour_min_temperature: Option<AdditiveValue<f64>>,
+    pub twenty_four_hour_temperature: Option<AdditiveValue<TemperatureExtremes>>,
+    pub pressure_tendency: Option<AdditiveValue<PressureTendency>>,
+    pub peak_wind: Option<PeakWind>,
+    pub wind_shift: Option<WindShift>,
+    /// `TWR VIS`, in statute miles.
+    pub tower_visibility: Option<f64>,
+    /// `SFC VIS`, in statute miles.
+    pub surface_visibility: Option<f64>,
+    pub variable_visibility: Option<VariableVisibility>,
+    pub sector_visibility: Vec<SectorVisibility>,
+    pub variable_ceiling: Option<VariableCeiling>,
+    pub weather_events: Vec<WeatherEvent>,
+    pub phenomena_locations: Vec<PhenomenonLocation>,
+    pub lightning: Vec<Lightning>,
+    pub sensor_status: SensorStatus,
+    /// Remarks that are not decoded, in the order they appeared.
+    pub unparsed: Vec<String>,
+}
+
+pub(crate) enum Remark {
+    StationType(StationType),
+    SeaLevelPressure(SeaLevelPressure),
+    PreciseTemperature(PreciseTemperature),
+    HourlyPrecipitation(AdditiveValue<f64>),
+    ThreeOrSixHourPrecipitation(AdditiveValue<f64>),
+    TwentyFourHourPrecipitation(AdditiveValue<f64>),
+    SnowDepth(AdditiveValue<u16>),
+    SixHourMaxTemperature(AdditiveValue<f64>),
+    SixHourMinTemperature(AdditiveValue<f64>),
+    TwentyFourHourTemperature(AdditiveValue<TemperatureExtremes>),
+    PressureTendency(AdditiveValue<PressureTendency>),
+    PeakWind(PeakWind),
+    WindShift(WindShift),
+    TowerVisibility(f64),
+    SurfaceVisibility(f64),
+    VariableVisibility(VariableVisibility),
+    SectorVisibility(SectorVisibility),
+    VariableCeiling(VariableCeiling),
+    WeatherEvents(Vec<WeatherEvent>),
+    PhenomenonLocation(PhenomenonLocation),
+    Lightning(Lightning),
+    SensorOutage(SensorOutage),
+    Maintenance,
+    Unparsed(String),
+}
+
+impl Remarks {
+    /// Stores a decoded remark. A repeated group that has room for a single
+    /// value is kept as `text` in [`Remarks::unparsed`] rather than replacing
+    /// the first one.
+    pub(crate) fn push(&mut self, remark: Remark, text: &str) {
+        let kept = match remark {
+            Remark::StationType(t) => fill(&mut self.station_type, t),
+            Remark::SeaLevelPressure(p) => fill(&mut self.sea_level_pressure, p),
+            Remark::PreciseTemperature(t) => fill(&mut self.precise_temperature, t),
+            Remark::HourlyPrecipitation(p) => fill(&mut self.hourly_precipitation, p),
+            Remark::ThreeOrSixHourPrecipitation(p) => {
+                fill(&mut self.three_or_six_hour_precipitation, p)
+            }
+            Remark::TwentyFourHourPrecipitation(p) => {
+                fill(&mut self.twenty_four_hour_precipitation, p)
+            }
+            Remark::SnowDepth(d) => fill(&mut self.snow_depth, d),
+            Remark::SixHourMaxTemperature(t) => fill(&mut self.six_hour_max_temperature, t),
+            Remark::SixHourMinTemperature(t) => fill(&mut self.six_hour_min_temperature, t),
+            Remark::TwentyFourHourTemperature(t) => fill(&mut self.twenty_four_hour_temperature, t),
+            Remark::PressureTendency(p) => fill(&mut self.pressure_tendency, p),
+            Remark::PeakWind(w) => fill(&mut self.peak_wind, w),
+            Remark::WindShift(w) => fill(&mut self.wind_shift, w),
+            Remark::TowerVisibility(v) => fill(&mut self.tower_visibility, v),
+            Remark::SurfaceVisibility(v) => fill(&mut self.surface_visibility, v),
+            Remark::VariableVisibility(v) => fill(&mut self.variable_visibility, v),
+            Remark::SectorVisibility(v) => {
+                self.sector_visibility.push(v);
+                true
+            }
+            Remark::VariableCeiling(c) => fill(&mut self.variable_ceiling, c),
+            Remark::WeatherEvents(e) => {
+                self.weather_events.extend(e);
+                true
+            }
+            Remark::PhenomenonLocation(p) => {
+                self.phenomena_locations.push(p);
+                true
+            }
+            Remark::Lightning(l) => {
+                self.lightning.push(l);
+                true
+            }
+            Remark::SensorOutage(o) => {
+                self.sensor_status.outages.push(o);
+                true
+            }
+            Remark::Maintenance => {
+                self.sensor_status.maintenance_required = true;
+                true
+            }
+            Remark::Unparsed(r) => {
+                self.unparsed.push(r);
+                true
+            }
+        };
+        if !kept {
+            self.unparsed.push(text.trim().to_owned());
+        }
+    }
+}
+
+/// Stores `value` in `slot` unless it already holds one; returns whether it
+/// was stored.
+fn fill<T>(slot: &mut Option<T>, value: T) -> bool {
+    if slot.is_some() {
+        return false;
+    }
+    *slot = Some(value);
+    true
+}
+
+/// Writes a signed value in tenths as read by [`parse_tenths`], e.g. `1006`
+/// for -0.6. The sign of -0.0 is kept, as it is for `M00`.
+fn fmt_tenths(value: &f64, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    let sign = if value.is_sign_negative() { 1 } else { 0 };
+    write!(f, "{}{:03}", sign, (value.abs() * 10.0).round() as u16)
+}
+
+/// Writes precipitation in inches, in hundredths on four digits.
+fn fmt_hundredths(value: &f64, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    write!(f, "{:04}", (value * 100.0).round() as u16)
+}
+
+/// An additive data group: `prefix` followed by the value written with
+/// `encode`, or by `width` slashes when it is missing.
+struct Additive<'a, T> {
+    prefix: &'static str,
+    width: usize,
+    value: &'a AdditiveValue<T>,
+    encode: fn(&T, &mut fmt::Formatter<'_>) -> fmt::Result,
+}
+
+impl<'a, T> Additive<'a, T> {
+    fn new(
+        prefix: &'static str,
+        width: usize,
+        value: &'a AdditiveValue<T>,
+        encode: fn(&T, &mut fmt::Formatter<'_>) -> fmt::Result,
+    ) -> Self {
+        Additive {
+            prefix,
+            width,
+            value,
+            encode,
+        }
+    }
+}
+
+impl<T> fmt::Display for Additive<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.prefix)?;
+        match self.value {
+            AdditiveValue::Value(value) => (self.encode)(value, f),
+            AdditiveValue::Missing => write!(f, "{:/<width$}", "", width = self.width),
+        }
+    }
+}
+
+/// The `LTG` group followed by the lightning types, e.g. `LTGICCG`.
+struct LightningGroup<'a>(&'a [LightningType]);
+
+impl fmt::Display for LightningGroup<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("LTG")?;
+        self.0.iter().try_for_each(|t| write!(f, "{}", t))
+    }
+}
+
+/// Statute miles as written in visibility remarks, without the `SM` unit.
+struct Miles(f64);
+
+impl fmt::Display for Miles {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt_statute_miles(self.0, f)
+    }
+}
+
+impl fmt::Display for StationType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            StationType::Ao1 => f.write_str("AO1"),
+            StationType::Ao2 => f.write_str("AO2"),
+        }
+    }
+}
+
+/// Writes the last three digits of the pressure in tenths, e.g. `SLP132`
+/// for 1013.2 hPa.
+impl fmt::Display for SeaLevelPressure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SeaLevelPressure::Hpa(hpa) => {
+                write!(f, "SLP{:03}", (hpa * 10.0).round() as u32 % 1000)
+            }
+            SeaLevelPressure::NotAvailable => f.write_str("SLPNO"),
+        }
+    }
+}
+
+impl fmt::Display for PreciseTemperature {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("T")?;
+        fmt_tenths(&self.air, f)?;
+        match &self.dew_point {
+            Some(dew_point) => fmt_tenths(dew_point, f),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Writes the value of a `4` group, without the group indicator.
+impl fmt::Display for TemperatureExtremes {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt_tenths(&self.max, f)?;
+        fmt_tenths(&self.min, f)
+    }
+}
+
+/// Writes the value of a `5` group, without the group indicator.
+impl fmt::Display for PressureTendency {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}{:03}",
+            self.character,
+            (self.change * 10.0).round() as u16
+        )
+    }
+}
+
+impl fmt::Display for RemarkTime {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(hour) = self.hour {
+            write!(f, "{:02}", hour)?;
+        }
+        write!(f, "{:02}", self.minute)
+    }
+}
+
+impl fmt::Display for PeakWind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "PK WND {}{}/{}",
+            self.wind.direction, self.wind.speed, self.time
+        )
+    }
+}
+
+impl fmt::Display for WindShift {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "WSHFT {}", self.time)?;
+        if self.frontal_passage {
+            f.write_str(" FROPA")?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for VariableVisibility {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "VIS {}V{}", Miles(self.min), Miles(self.max))
+    }
+}
+
+impl fmt::Display for SectorVisibility {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "VIS {} {}", self.direction, Miles(self.visibility))
+    }
+}
+
+impl fmt::Display for VariableCeiling {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "CIG {:03}V{:03}", self.min, self.max)
+    }
+}
+
+impl fmt::Display for WeatherEventKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            WeatherEventKind::Began => f.write_str("B"),
+            WeatherEventKind::Ended => f.write_str("E"),
+        }
+    }
+}
+
+impl fmt::Display for WeatherEventTime {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}{}", self.kind, self.time)
+    }
+}
+
+impl fmt::Display for WeatherEvent {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt_weather(&self.descriptor, &self.phenomena, f)?;
+        self.times.iter().try_for_each(|time| write!(f, "{}", time))
+    }
+}
+
+impl fmt::Display for LocationDistance {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LocationDistance::Vicinity => f.write_str("VC"),
+            LocationDistance::Distant => f.write_str("DSNT"),
+        }
+    }
+}
+
+/// Directions are joined with `-`, which reads back the same as `AND`.
+impl fmt::Display for LocationArea {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LocationArea::Overhead => f.write_str("OHD"),
+            LocationArea::AllQuadrants => f.write_str("ALQDS"),
+            LocationArea::Directions(directions) => {
+                let mut separator = "";
+                for direction in directions {
+                    write!(f, "{}{}", separator, direction)?;
+                    separator = "-";
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+impl fmt::Display for RemarkLocation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.write_groups(&mut GroupWriter::new(f))
+    }
+}
+
+impl WriteGroups for RemarkLocation {
+    fn write_groups(&self, w: &mut GroupWriter<'_, '_>) -> fmt::Result {
+        w.groups(&self.distance)?;
+        w.groups(&self.area)
+    }
+}
+
+impl fmt::Display for PhenomenonKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let kind = match self {
+            PhenomenonKind::Thunderstorm => "TS",
+            PhenomenonKind::Cumulonimbus => "CB",
+            PhenomenonKind::CumulonimbusMammatus => "CBMAM",
+            PhenomenonKind::ToweringCumulus => "TCU",
+            PhenomenonKind::Virga => "VIRGA",
+        };
+        f.write_str(kind)
+    }
+}
+
+impl fmt::Display for PhenomenonLocation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut w = GroupWriter::new(f);
+        w.group(&self.kind)?;
+        w.section(&self.location)?;
+        if let Some(direction) = &self.movement {
+            w.group(format_args!("MOV {}", direction))?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for LightningFrequency {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let frequency = match self {
+            LightningFrequency::Occasional => "OCNL",
+            LightningFrequency::Frequent => "FRQ",
+            LightningFrequency::Continuous => "CONS",
+        };
+        f.write_str(frequency)
+    }
+}
+
+impl fmt::Display for LightningType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let lightning_type = match self {
+            LightningType::InCloud => "IC",
+            LightningType::CloudToCloud => "CC",
+            LightningType::CloudToGround => "CG",
+            LightningType::CloudToAir => "CA",
+        };
+        f.write_str(lightning_type)
+    }
+}
+
+impl fmt::Display for Lightning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut w = GroupWriter::new(f);
+        w.groups(&self.frequency)?;
+        w.group(LightningGroup(&self.types))?;
+        w.section(&self.location)
+    }
+}
+
+/// Writes the decoded remarks in the order of the US Federal Meteorological
+/// Handbook, followed by the remarks that were not decoded, the sensor
+/// outages and the `$` indicator. The original order is not kept.
+impl fmt::Display for Remarks {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.write_groups(&mut GroupWriter::new(f))
+    }
+}
+
+impl WriteGroups for Remarks {
+    fn write_groups(&self, w: &mut GroupWriter<'_, '_>) -> fmt::Result {
+        w.groups(&self.station_type)?;
+        w.groups(&self.peak_wind)?;
+        w.groups(&self.wind_shift)?;
+        if let Some(v) = self.tower_visibility {
+            w.group(format_args!("TWR VIS {}", Miles(v)))?;
+        }
+        if let Some(v) = self.surface_visibility {
+            w.group(format_args!("SFC VIS {}", Miles(v)))?;
+        }
+        w.groups(&self.variable_visibility)?;
+        w.groups(&self.sector_visibility)?;
+        w.groups(&self.lightning)?;
+        w.groups(&self.weather_events)?;
+        w.groups(&self.phenomena_locations)?;
+        w.groups(&self.variable_ceiling)?;
+        w.groups(&self.sea_level_pressure)?;
+        w.groups(
+            self.hourly_precipitation
+                .as_ref()
+                .map(|p| Additive::new("P", 4, p, fmt_hundredths)),
+        )?;
+        w.groups(
+            self.three_or_six_hour_precipitation
+                .as_ref()
+                .map(|p| Additive::new("6", 4, p, fmt_hundredths)),
+        )?;
+        w.groups(
+            self.twenty_four_hour_precipitation
+                .as_ref()
+                .map(|p| Additive::new("7", 4, p, fmt_hundredths)),
+        )?;
+        w.groups(
+            self.snow_depth
+                .as_ref()
+                .map(|d| Additive::new("4/", 3, d, |d, f| write!(f, "{:03}", d))),
+        )?;
+        w.groups(&self.precise_temperature)?;
+        w.groups(
+            self.six_hour_max_temperature
+                .as_ref()
+                .map(|t| Additive::new("1", 4, t, fmt_tenths)),
+        )?;
+        w.groups(
+            self.six_hour_min_temperature
+                .as_ref()
+                .map(|t| Additive::new("2", 4, t, fmt_tenths)),
+        )?;
+        w.groups(
+            self.twenty_four_hour_temperature
+                .as_ref()
+                .map(|t| Additive::new("4", 8, t, fmt::Display::fmt)),
+        )?;
+        w.groups(
+            self.pressure_tendency
+                .as_ref()
+                .map(|p| Additive::new("5", 4, p, fmt::Display::fmt)),
+        )?;
+        w.groups(&self.unparsed)?;
+        w.section(&self.sensor_status)
+    }
+}
+
+/// Splits a report into its body and the text following the `RMK` keyword.
+/// Both are slices of `s`, so that offsets into them can be computed.
+pub fn split_remarks(s: &str) -> (&str, Option<&str>) {
+    match s.split_whitespace().find(|token| *token == "RMK") {
+        Some(token) => {
+            let start = token.as_ptr() as usize - s.as_ptr() as usize;
+            (s[..start].trim_end(), Some(s[start + 3..].trim_start()))
+        }
+        None => (s, None),
+    }
+}
+
+fn digits(n: usize) -> impl FnMut(&str) -> IResult<&str, &str> {
+    move |s: &str| take_while_m_n(n, n, |c: char| c.is_ascii_digit())(s)
+}
+
+fn parse_station_type(s: &str) -> IResult<&str, StationType> {
+    map_opt(alt((tag("AO1"), tag("AO2"))), code)(s)
+}
+
+fn parse_sea_level_pressure(s: &str) -> IResult<&str, SeaLevelPressure> {
+    preceded(
+        tag("SLP"),
+        alt((
+            map(tag("NO"), |_| SeaLevelPressure::NotAvailable),
+            map(map_res(digits(3), str::parse::<u16>), |p| {
+                // Only the last three digits are sent: pick the century that
+                // puts the value closest to standard pressure.
+                let base = if p < 500 { 1000.0 } else { 900.0 };
+                SeaLevelPressure::Hpa(base + p as f64 / 10.0)
+            }),
+        )),
+    )(s)
+}
+
+/// Signed temperature in tenths of a degree, e.g. `1006` is -0.6 °C.
+pub(crate) fn parse_tenths(s: &str) -> IResult<&str, f64> {
+    map_res(
+        pair(alt((tag("0"), tag("1"))), digits(3)),
+        |(sign, tenths): (&str, &str)| {
+            tenths.parse::<u16>().map(|t| {
+                let t = t as f64 / 10.0;
+                if sign == "1" {
+                    -t
+                } else {
+                    t
+                }
+            })
+        },
+    )(s)
+}
+
+fn parse_precise_temperature(s: &str) -> IResult<&str, PreciseTemperature> {
+    map(
+        preceded(tag("T"), pair(parse_tenths, opt(parse_tenths))),
+        |(air, dew_point)| PreciseTemperature { air, dew_point },
+    )(s)
+}
+
+/// Parses an additive data value with `parser`, or `width` slashes when it
+/// is missing.
+fn additive<'a, T>(
+    width: usize,
+    parser: impl FnMut(&'a str) -> IResult<&'a str, T>,
+) -> impl FnMut(&'a str) -> IResult<&'a str, AdditiveValue<T>> {
+    alt((
+        map(parser, AdditiveValue::Value),
+        map(take_while_m_n(width, width, |c: char| c == '/'), |_| {
+            AdditiveValue::Missing
+        }),
+    ))
+}
+
+/// Precipitation amount in hundredths of an inch.
+fn parse_inches(s: &str) -> IResult<&str, f64> {
+    map(map_res(digits(4), str::parse::<u16>), |p| p as f64 / 100.0)(s)
+}
+
+fn parse_pressure_tendency(s: &str) -> IResult<&str, PressureTendency> {
+    map(
+        pair(
+            map_res(
+                take_while_m_n(1, 1, |c: char| ('0'..='8').contains(&c)),
+                str::parse,
+            ),
+            map_res(digits(3), str::parse::<u16>),
+        ),
+        |(character, change)| PressureTendency {
+            character,
+            change: change as f64 / 10.0,
+        },
+    )(s)
+}
+
+fn parse_additive_data(s: &str) -> IResult<&str, Remark> {
+    alt((
+        map(
+            preceded(tag("P"), additive(4, parse_inches)),
+            Remark::HourlyPrecipitation,
+        ),
+        map(
+            preceded(tag("6"), additive(4, parse_inches)),
+            Remark::ThreeOrSixHourPrecipitation,
+        ),
+        map(
+            preceded(tag("7"), additive(4, parse_inches)),
+            Remark::TwentyFourHourPrecipitation,
+        ),
+        map(
+            preceded(tag("1"), additive(4, parse_tenths)),
+            Remark::SixHourMaxTemperature,
+        ),
+        map(
+            preceded(tag("2"), additive(4, parse_tenths)),
+            Remark::SixHourMinTemperature,
+        ),
+        map(
+            preceded(
+                tag("4"),
+                additive(
+                    8,
+                    map(pair(parse_tenths, parse_tenths), |(max, min)| {
+                        TemperatureExtremes { max, min }
+                    }),
+                ),
+            ),
+            Remark::TwentyFourHourTemperature,
+        ),
+        map(
+            preceded(
+                tag("4/"),
+                additive(3, map_res(digits(3), str::parse::<u16>)),
+            ),
+            Remark::SnowDepth,
+        ),
+        map(
+            preceded(tag("5"), additive(4, parse_pressure_tendency)),
+            Remark::PressureTendency,
+        ),
+    ))(s)
+}
+
+/// `hhmm`, or `mm` when the hour is the same as the report's.
+pub(crate) fn parse_remark_time(s: &str) -> IResult<&str, RemarkTime> {
+    alt((
+        map_opt(pair(digits(2), digits(2)), |(hour, minute)| {
+            Some(RemarkTime {
+                hour: Some(parse_with_bounds(0, 23, hour)?),
+                minute: parse_with_bounds(0, 59, minute)?,
+            })
+        }),
+        map_opt(digits(2), |minute| {
+            Some(RemarkTime {
+                hour: None,
+                minute: parse_with_bounds(0, 59, minute)?,
+            })
+        }),
+    ))(s)
+}
+
+fn parse_peak_wind(s: &str) -> IResult<&str, PeakWind> {
+    map(
+        preceded(
+            tuple((tag("PK"), multispace1, tag("WND"), multispace1)),
+            separated_pair(
+                pair(
+                    map_res(digits(3), str::parse::<u16>),
+                    map_res(
+                        take_while_m_n(2, 3, |c: char| c.is_ascii_digit()),
+                        str::parse::<u16>,
+                    ),
+                ),
+                tag("/"),
+                parse_remark_time,
+            ),
+        ),
+        |((direction, speed), time)| PeakWind {
+            wind: Wind {
+                direction: WindDirection::Direct(direction),
+                speed: WindSpeed::Exact(speed),
+                gust_speed: None,
+                unit: WindUnit::Kt,
+                variable_direction: None,
+            },
+            time,
+        },
+    )(s)
+}
+
+fn parse_wind_shift(s: &str) -> IResult<&str, WindShift> {
+    map(
+        preceded(
+            pair(tag("WSHFT"), multispace1),
+            pair(parse_remark_time, opt(preceded(multispace1, tag("FROPA")))),
+        ),
+        |(time, fropa)| WindShift {
+            time,
+            frontal_passage: fropa.is_some(),
+        },
+    )(s)
+}
+
+fn parse_visibility_remark(s: &str) -> IResult<&str, Remark> {
+    alt((
+        map(
+            preceded(
+                tuple((tag("TWR"), multispace1, tag("VIS"), multispace1)),
+                parse_statute_miles,
+            ),
+            Remark::TowerVisibility,
+        ),
+        map(
+            preceded(
+                tuple((tag("SFC"), multispace1, tag("VIS"), multispace1)),
+                parse_statute_miles,
+            ),
+            Remark::SurfaceVisibility,
+        ),
+        preceded(
+            pair(tag("VIS"), multispace1),
+            alt((
+                map(
+                    separated_pair(parse_direction, multispace1, parse_statute_miles),
+                    |(direction, visibility)| {
+                        Remark::SectorVisibility(SectorVisibility {
+                            direction,
+                            visibility,
+                        })
+                    },
+                ),
+                map(
+                    separated_pair(parse_statute_miles, tag("V"), parse_statute_miles),
+                    |(min, max)| Remark::VariableVisibility(VariableVisibility { min, max }),
+                ),
+            )),
+        ),
+    ))(s)
+}
+
+fn parse_variable_ceiling(s: &str) -> IResult<&str, VariableCeiling> {
+    map(
+        preceded(
+            pair(tag("CIG"), multispace1),
+            separated_pair(
+                map_res(digits(3), str::parse),
+                tag("V"),
+                map_res(digits(3), str::parse),
+            ),
+        ),
+        |(min, max)| VariableCeiling { min, max },
+    )(s)
+}
+
+fn parse_weather_event_time(s: &str) -> IResult<&str, WeatherEventTime> {
+    map(
+        pair(
+            alt((
+                map(tag("B"), |_| WeatherEventKind::Began),
+                map(tag("E"), |_| WeatherEventKind::Ended),
+            )),
+            parse_remark_time,
+        ),
+        |(kind, time)| WeatherEventTime { kind, time },
+    )(s)
+}
+
+fn parse_weather_event(s: &str) -> IResult<&str, WeatherEvent> {
+    map(
+        pair(
+            verify(
+                pair(opt(parse_descriptor), many0(parse_phenomenon)),
+                |(descriptor, phenomena)| descriptor.is_some() || !phenomena.is_empty(),
+            ),
+            many1(parse_weather_event_time),
+        ),
+        |((descriptor, phenomena), times)| WeatherEvent {
+            descriptor,
+            phenomena,
+            times,
+        },
+    )(s)
+}
+
+/// A compass direction that ends at a group boundary or a `-` range
+/// separator, so that `S` is not read out of `SFC`.
+fn parse_direction_token(s: &str) -> IResult<&str, VisibilityDirection> {
+    terminated(
+        parse_direction,
+        alt((end_of_group, value((), peek(tag("-"))))),
+    )(s)
+}
+
+fn parse_location_area(s: &str) -> IResult<&str, LocationArea> {
+    alt((
+        map(tag("OHD"), |_| LocationArea::Overhead),
+        map(tag("ALQDS"), |_| LocationArea::AllQuadrants),
+        map(
+            separated_list1(
+                alt((
+                    tag("-"),
+                    recognize(tuple((multispace1, tag("AND"), multispace1))),
+                )),
+                parse_direction_token,
+            ),
+            LocationArea::Directions,
+        ),
+    ))(s)
+}
+
+fn parse_location(s: &str) -> IResult<&str, RemarkLocation> {
+    map(
+        pair(
+            opt(preceded(
+                multispace1,
+                alt((
+                    map(tag("VC"), |_| LocationDistance::Vicinity),
+                    map(tag("DSNT"), |_| LocationDistance::Distant),
+                )),
+            )),
+            opt(preceded(multispace1, parse_location_area)),
+        ),
+        |(distance, area)| RemarkLocation { distance, area },
+    )(s)
+}
+
+fn parse_phenomenon_location(s: &str) -> IResult<&str, PhenomenonLocation> {
+    map(
+        tuple((
+            map_opt(
+                alt((tag("TS"), tag("CBMAM"), tag("CB"), tag("TCU"), tag("VIRGA"))),
+                code,
+            ),
+            parse_location,
+            opt(preceded(
+                tuple((multispace1, tag("MOV"), multispace1)),
+                parse_direction_token,
+            )),
+        )),
+        |(kind, location, movement)| PhenomenonLocation {
+            kind,
+            location,
+            movement,
+        },
+    )(s)
+}
+
+fn parse_lightning(s: &str) -> IResult<&str, Lightning> {
+    map(
+        tuple((
+            opt(terminated(
+                map_opt(alt((tag("OCNL"), tag("FRQ"), tag("CONS"))), code),
+                multispace1,
+            )),
+            preceded(
+                tag("LTG"),
+                many0(map_opt(
+                    alt((tag("IC"), tag("CC"), tag("CG"), tag("CA"))),
+                    code,
+                )),
+            ),
+            parse_location,
+        )),
+        |(frequency, types, location)| Lightning {
+            frequency,
+            types,
+            location,
+        },
+    )(s)
+}
+
+pub(crate) fn parse_remark(s: &str) -> IResult<&str, Remark> {
+    let s = s.trim_start();
+    alt((
+        terminated(
+            alt((
+                map(parse_station_type, Remark::StationType),
+                map(parse_sea_level_pressure, Remark::SeaLevelPressure),
+                map(parse_precise_temperature, Remark::PreciseTemperature),
+                parse_additive_data,
+                map(parse_peak_wind, Remark::PeakWind),
+                map(parse_wind_shift, Remark::WindShift),
+                parse_visibility_remark,
+                map(parse_variable_ceiling, Remark::VariableCeiling),
+                map(parse_sensor_outage, Remark::SensorOutage),
+                map(tag("$"), |_| Remark::Maintenance),
+                map(many1(parse_weather_event), Remark::WeatherEvents),
+                map(parse_phenomenon_location, Remark::PhenomenonLocation),
+                map(parse_lightning, Remark::Lightning),
+            )),
+            end_of_group,
+        ),
+        map(take_till1(char::is_whitespace), |r: &str| {
+            Remark::Unparsed(r.to_owned())
+        }),
+    ))(s)
+}
+
+pub fn parse_remarks(s: &str) -> IResult<&str, Remarks> {
+    let (rest, remarks) = many0(consumed(parse_remark))(s)?;
+    let remarks = remarks
+        .into_iter()
+        .fold(Remarks::default(), |mut acc, (text, r)| {
+            acc.push(r, text);
+            acc
+        });
+    Ok((rest, remarks))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::sensor::SensorKind;
+
+    #[test]
+    fn test_split_remarks() {
+        assert_eq!(
+            split_remarks("KJFK 141651Z 22010KT 10SM A2992 RMK AO2 SLP132"),
+            ("KJFK 141651Z 22010KT 10SM A2992", Some("AO2 SLP132"))
+        );
+        assert_eq!(
+            split_remarks("KJFK 141651Z 22010KT 10SM A2992"),
+            ("KJFK 141651Z 22010KT 10SM A2992", None)
+        );
+        assert_eq!(split_remarks("RMK AO1"), ("", Some("AO1")));
+        assert_eq!(split_remarks("KJFK A2992 RMKX"), ("KJFK A2992 RMKX", None));
+        assert_eq!(
+            split_remarks("KJFK Q1013\tRMK AO2"),
+            ("KJFK Q1013", Some("AO2"))
+        );
+        assert_eq!(split_remarks("KJFK Q1013\nRMK"), ("KJFK Q1013", Some("")));
+    }
+
+    #[test]
+    fn test_parse_remarks() -> anyhow::Result<()> {
+        let (rest, remarks) = parse_remarks("AO2 SLP132 T00830033")?;
+        assert_eq!(rest, "");
+        assert_eq!(
+            remarks,
+            Remarks {
+                station_type: Some(StationType::Ao2),
+                sea_level_pressure: Some(SeaLevelPressure::Hpa(1013.2)),
+                precise_temperature: Some(PreciseTemperature {
+                    air: 8.3,
+                    dew_point: Some(3.3)
+                }),
+                unparsed: vec![],
+                ..Default::default()
+            }
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_repeated_remarks() -> anyhow::Result<()> {
+        let (_, remarks) = parse_remarks("AO2 SLP132  SLP133 T00830033 T00840034 AO1")?;
+        assert_eq!(remarks.station_type, Some(StationType::Ao2));
+        assert_eq!(
+            remarks.sea_level_pressure,
+            Some(SeaLevelPressure::Hpa(1013.2))
+        );
+        assert_eq!(remarks.precise_temperature.map(|t| t.air), Some(8.3));
+        assert_eq!(remarks.unparsed, vec!["SLP133", "T00840034", "AO1"]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_remarks_edge_cases() -> anyhow::Result<()> {
+        let (_, remarks) = parse_remarks("AO1 SLPNO T10061017 ESTMD WND DATA")?;
+        assert_eq!(remarks.station_type, Some(StationType::Ao1));
+        assert_eq!(
+            remarks.sea_level_pressure,
+            Some(SeaLevelPressure::NotAvailable)
+        );
+        assert_eq!(
+            remarks.precise_temperature,
+            Some(PreciseTemperature {
+                air: -0.6,
+                dew_point: Some(-1.7)
+            })
+        );
+        assert_eq!(remarks.unparsed, vec!["ESTMD", "WND", "DATA"]);
+
+        let (_, remarks) = parse_remarks("SLP982 T0123 AO2X")?;
+        assert_eq!(
+            remarks.sea_level_pressure,
+            Some(SeaLevelPressure::Hpa(998.2))
+        );
+        assert_eq!(
+            remarks.precise_temperature,
+            Some(PreciseTemperature {
+                air: 12.3,
+                dew_point: None
+            })
+        );
+        assert_eq!(remarks.unparsed, vec!["AO2X"]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_additive_data() -> anyhow::Result<()> {
+        let (_, remarks) =
+            parse_remarks("AO2 P0012 60025 70125 4/021 10142 21001 401120084 52032")?;
+        assert_eq!(
+            remarks.hourly_precipitation,
+            Some(AdditiveValue::Value(0.12))
+        );
+        assert_eq!(
+            remarks.three_or_six_hour_precipitation,
+            Some(AdditiveValue::Value(0.25))
+        );
+        assert_eq!(
+            remarks.twenty_four_hour_precipitation,
+            Some(AdditiveValue::Value(1.25))
+        );
+        assert_eq!(remarks.snow_depth, Some(AdditiveValue::Value(21)));
+        assert_eq!(
+            remarks.six_hour_max_temperature,
+            Some(AdditiveValue::Value(14.2))
+        );
+        assert_eq!(
+            remarks.six_hour_min_temperature,
+            Some(AdditiveValue::Value(-0.1))
+        );
+        assert_eq!(
+            remarks.twenty_four_hour_temperature,
+            Some(AdditiveValue::Value(TemperatureExtremes {
+                max: 11.2,
+                min: 8.4
+            }))
+        );
+        assert_eq!(
+            remarks.pressure_tendency,
+            Some(AdditiveValue::Value(PressureTendency {
+                character: 2,
+                change: 3.2
+            }))
+        );
+        assert!(remarks.unparsed.is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn test_repeated_additive_data() -> anyhow::Result<()> {
+        let (_, remarks) = parse_remarks("P0012 P0015 60025 60030 52032 58033")?;
+        assert_eq!(
+            remarks.hourly_precipitation,
+            Some(AdditiveValue::Value(0.12))
+        );
+        assert_eq!(
+            remarks.three_or_six_hour_precipitation,
+            Some(AdditiveValue::Value(0.25))
+        );
+        assert_eq!(
+            remarks.pressure_tendency,
+            Some(AdditiveValue::Value(PressureTendency {
+                character: 2,
+                change: 3.2
+            }))
+        );
+        assert_eq!(remarks.unparsed, vec!["P0015", "60030", "58033"]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_additive_data_missing() -> anyhow::Result<()> {
+        let (_, remarks) = parse_remarks("P//// 6//// 7//// 4//// 1//// 2//// 4//////// 5////")?;
+        assert_eq!(remarks.hourly_precipitation, Some(AdditiveValue::Missing));
+        assert_eq!(
+            remarks.three_or_six_hour_precipitation,
+            Some(AdditiveValue::Missing)
+        );
+        assert_eq!(
+            remarks.twenty_four_hour_precipitation,
+            Some(AdditiveValue::Missing)
+        );
+        assert_eq!(remarks.snow_depth, Some(AdditiveValue::Missing));
+        assert_eq!(
+            remarks.six_hour_max_temperature,
+            Some(AdditiveValue::Missing)
+        );
+        assert_eq!(
+            remarks.six_hour_min_temperature,
+            Some(AdditiveValue::Missing)
+        );
+        assert_eq!(
+            remarks.twenty_four_hour_temperature,
+            Some(AdditiveValue::Missing)
+        );
+        assert_eq!(remarks.pressure_tendency, Some(AdditiveValue::Missing));
+        assert!(remarks.unparsed.is_empty());
+
+        let (_, remarks) = parse_remarks("59999 600251")?;
+        assert_eq!(remarks.unparsed, vec!["59999", "600251"]);
+        assert_eq!(
+            PressureTendency {
+                character: 7,
+                change: 1.5
+            }
+            .signed_change(),
+            -1.5
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_visibility_remarks_before_snow_depth() -> anyhow::Result<()> {
+        let (_, remarks) = parse_remarks("TWR VIS 2 4/021")?;
+        assert_eq!(remarks.tower_visibility, Some(2.0));
+        assert_eq!(remarks.snow_depth, Some(AdditiveValue::Value(21)));
+
+        let (_, remarks) = parse_remarks("SFC VIS 1 4/021")?;
+        assert_eq!(remarks.surface_visibility, Some(1.0));
+        assert_eq!(remarks.snow_depth, Some(AdditiveValue::Value(21)));
+
+        let (_, remarks) = parse_remarks("VIS 1V2 4/021")?;
+        assert_eq!(
+            remarks.variable_visibility,
+            Some(VariableVisibility { min: 1.0, max: 2.0 })
+        );
+        assert_eq!(remarks.snow_depth, Some(AdditiveValue::Value(21)));
+
+        let (_, remarks) = parse_remarks("VIS NE 2 4/021")?;
+        assert_eq!(
+            remarks.sector_visibility,
+            vec![SectorVisibility {
+                direction: VisibilityDirection::NorthEast,
+                visibility: 2.0
+            }]
+        );
+        assert_eq!(remarks.snow_depth, Some(AdditiveValue::Value(21)));
+
+        let (_, remarks) = parse_remarks("TWR VIS 1 1/2 4/021")?;
+        assert_eq!(remarks.tower_visibility, Some(1.5));
+        assert_eq!(remarks.snow_depth, Some(AdditiveValue::Value(21)));
+        Ok(())
+    }
+
+    #[test]
+    fn test_repeated_wind_and_visibility_remarks() -> anyhow::Result<()> {
+        let sample = "PK WND 28045/1955 PK WND 29050/2000 WSHFT 1715 WSHFT 1730 \
+                      TWR VIS 1 TWR VIS 2 SFC VIS 1/4 SFC VIS 1/2 VIS 1/2V2 VIS 1V3 \
+                      CIG 005V010 CIG 006V012";
+        let (_, remarks) = parse_remarks(sample)?;
+        assert_eq!(remarks.peak_wind.as_ref().map(|p| p.time.minute), Some(55));
+        assert_eq!(remarks.wind_shift.as_ref().map(|w| w.time.minute), Some(15));
+        assert_eq!(remarks.tower_visibility, Some(1.0));
+        assert_eq!(remarks.surface_visibility, Some(0.25));
+        assert_eq!(
+            remarks.variable_visibility,
+            Some(VariableVisibility { min: 0.5, max: 2.0 })
+        );
+        assert_eq!(
+            remarks.variable_ceiling,
+            Some(VariableCeiling { min: 5, max: 10 })
+        );
+        assert_eq!(
+            remarks.unparsed,
+            vec![
+                "PK WND 29050/2000",
+                "WSHFT 1730",
+                "TWR VIS 2",
+                "SFC VIS 1/2",
+                "VIS 1V3",
+                "CIG 006V012"
+            ]
+        );
+        let encoded = remarks.to_string();
+        assert_eq!(parse_remarks(&encoded).unwrap().1, remarks);
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_wind_and_visibility_remarks() -> anyhow::Result<()> {
+        let (_, remarks) = parse_remarks(
+            "AO2 PK WND 28045/1955 WSHFT 1715 FROPA TWR VIS 1 1/2 SFC VIS 1/4 VIS 1/2V2 VIS NE 2 1/2 CIG 005V010",
+        )?;
+        assert_eq!(
+            remarks.peak_wind,
+            Some(PeakWind {
+                wind: Wind {
+                    direction: WindDirection::Direct(280),
+                    speed: WindSpeed::Exact(45),
+                    gust_speed: None,
+                    unit: WindUnit::Kt,
+                    variable_direction: None
+                },
+                time: RemarkTime {
+                    hour: Some(19),
+                    minute: 55
+                }
+            })
+        );
+        assert_eq!(
+            remarks.wind_shift,
+            Some(WindShift {
+                time: RemarkTime {
+                    hour: Some(17),
+                    minute: 15
+                },
+                frontal_passage: true
+            })
+        );
+        assert_eq!(remarks.tower_visibility, Some(1.5));
+        assert_eq!(remarks.surface_visibility, Some(0.25));
+        assert_eq!(
+            remarks.variable_visibility,
+            Some(VariableVisibility { min: 0.5, max: 2.0 })
+        );
+        assert_eq!(
+            remarks.sector_visibility,
+            vec![SectorVisibility {
+                direction: VisibilityDirection::NorthEast,
+                visibility: 2.5
+            }]
+        );
+        assert_eq!(
+            remarks.variable_ceiling,
+            Some(VariableCeiling { min: 5, max: 10 })
+        );
+        assert!(remarks.unparsed.is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_short_remark_times() -> anyhow::Result<()> {
+        let (_, remarks) = parse_remarks("PK WND 031105/05 WSHFT 30")?;
+        assert_eq!(
+            remarks.peak_wind.map(|p| (p.wind.speed.value(), p.time)),
+            Some((
+                Some(105),
+                RemarkTime {
+                    hour: None,
+                    minute: 5
+                }
+            ))
+        );
+        assert_eq!(remarks.wind_shift.map(|w| w.frontal_passage), Some(false));
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_weather_events() -> anyhow::Result<()> {
+        let (_, remarks) = parse_remarks("RAB15E30SNB30 TSB0159E30")?;
+        assert_eq!(
+            remarks.weather_events,
+            vec![
+                WeatherEvent {
+                    descriptor: None,
+                    phenomena: vec![WeatherPhenomenon::Rain],
+                    times: vec![
+                        WeatherEventTime {
+                            kind: WeatherEventKind::Began,
+                            time: RemarkTime {
+                                hour: None,
+                                minute: 15
+                            }
+                        },
+                        WeatherEventTime {
+                            kind: WeatherEventKind::Ended,
+                            time: RemarkTime {
+                                hour: None,
+                                minute: 30
+                            }
+                        }
+                    ]
+                },
+                WeatherEvent {
+                    descriptor: None,
+                    phenomena: vec![WeatherPhenomenon::Snow],
+                    times: vec![WeatherEventTime {
+                        kind: WeatherEventKind::Began,
+                        time: RemarkTime {
+                            hour: None,
+                            minute: 30
+                        }
+                    }]
+                },
+                WeatherEvent {
+                    descriptor: Some(WeatherDescriptor::Thunderstorm),
+                    phenomena: vec![],
+                    times: vec![
+                        WeatherEventTime {
+                            kind: WeatherEventKind::Began,
+                            time: RemarkTime {
+                                hour: Some(1),
+                                minute: 59
+                            }
+                        },
+                        WeatherEventTime {
+                            kind: WeatherEventKind::Ended,
+                            time: RemarkTime {
+                                hour: None,
+                                minute: 30
+                            }
+                        }
+                    ]
+                }
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_phenomena_locations() -> anyhow::Result<()> {
+        let (_, remarks) = parse_remarks("TS OHD MOV NE CB W MOV E VIRGA SW TCU DSNT N-E SLP132")?;
+        assert_eq!(
+            remarks.phenomena_locations,
+            vec![
+                PhenomenonLocation {
+                    kind: PhenomenonKind::Thunderstorm,
+                    location: RemarkLocation {
+                        distance: None,
+                        area: Some(LocationArea::Overhead)
+                    },
+                    movement: Some(VisibilityDirection::NorthEast)
+                },
+                PhenomenonLocation {
+                    kind: PhenomenonKind::Cumulonimbus,
+                    location: RemarkLocation {
+                        distance: None,
+                        area: Some(LocationArea::Directions(vec![VisibilityDirection::West]))
+                    },
+                    movement: Some(VisibilityDirection::East)
+                },
+                PhenomenonLocation {
+                    kind: PhenomenonKind::Virga,
+                    location: RemarkLocation {
+                        distance: None,
+                        area: Some(LocationArea::Directions(vec![
+                            VisibilityDirection::SouthWest
+                        ]))
+                    },
+                    movement: None
+                },
+                PhenomenonLocation {
+                    kind: PhenomenonKind::ToweringCumulus,
+                    location: RemarkLocation {
+                        distance: Some(LocationDistance::Distant),
+                        area: Some(LocationArea::Directions(vec![
+                            VisibilityDirection::North,
+                            VisibilityDirection::East
+                        ]))
+                    },
+                    movement: None
+                }
+            ]
+        );
+        assert_eq!(
+            remarks.sea_level_pressure,
+            Some(SeaLevelPressure::Hpa(1013.2))
+        );
+        assert!(remarks.unparsed.is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_lightning() -> anyhow::Result<()> {
+        let (_, remarks) = parse_remarks("OCNL LTGICCG NW FRQ LTG VC LTGCG E AND W")?;
+        assert_eq!(
+            remarks.lightning,
+            vec![
+                Lightning {
+                    frequency: Some(LightningFrequency::Occasional),
+                    types: vec![LightningType::InCloud, LightningType::CloudToGround],
+                    location: RemarkLocation {
+                        distance: None,
+                        area: Some(LocationArea::Directions(vec![
+                            VisibilityDirection::NorthWest
+                        ]))
+                    }
+                },
+                Lightning {
+                    frequency: Some(LightningFrequency::Frequent),
+                    types: vec![],
+                    location: RemarkLocation {
+                        distance: Some(LocationDistance::Vicinity),
+                        area: None
+                    }
+                },
+                Lightning {
+                    frequency: None,
+                    types: vec![LightningType::CloudToGround],
+                    location: RemarkLocation {
+                        distance: None,
+                        area: Some(LocationArea::Directions(vec![
+                            VisibilityDirection::East,
+                            VisibilityDirection::West
+                        ]))
+                    }
+                }
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_sensor_status() -> anyhow::Result<()> {
+        let (_, remarks) =
+            parse_remarks("AO2 RVRNO PWINO PNO FZRANO TSNO VISNO RWY06 CHINO SLP132 $")?;
+        let status = &remarks.sensor_status;
+        assert_eq!(status.outages.len(), 7);
+        assert!(status.is_out(SensorKind::Lightning));
+        assert!(status.is_out(SensorKind::Ceiling));
+        assert!(status.maintenance_required);
+        assert_eq!(
+            remarks.sea_level_pressure,
+            Some(SeaLevelPressure::Hpa(1013.2))
+        );
+        assert!(remarks.unparsed.is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn test_display_remarks() -> anyhow::Result<()> {
+        for sample in [
+            "AO2 SLP132 T00830033",
+            "AO1 SLPNO T10061017 ESTMD WND DATA",
+            "AO2 P0012 60025 70125 4/021 10142 21001 401120084 52032",
+            "P//// 6//// 7//// 4//// 1//// 2//// 4//////// 5////",
+            "AO2 PK WND 28045/1955 WSHFT 1715 FROPA TWR VIS 1 1/2 SFC VIS 1/4 VIS 1/2V2 VIS NE 2 1/2 CIG 005V010",
+            "PK WND 031105/05 WSHFT 30",
+            "OCNL LTGICCG NW FRQ LTG VC LTGCG E-W RAB15E30 SNB30 TSB0159E30",
+            "TS OHD MOV NE CB W MOV E VIRGA SW TCU DSNT N-E SLP132",
+            "AO2 SLP132 RVRNO PWINO PNO FZRANO TSNO VISNO RWY06 CHINO $",
+            "T10001000 11000 21000 410001000",
+        ] {
+            let remarks = parse_remarks(sample)?.1;
+            assert_eq!(remarks.to_string(), sample);
+        }
+
+        let remarks = parse_remarks("$ SLP982 T0123 AO2X FRQ LTG E AND W")?.1;
+        assert_eq!(remarks.to_string(), "FRQ LTG E-W SLP982 T0123 AO2X $");
+        let encoded = remarks.to_string();
+        assert_eq!(parse_remarks(&encoded).unwrap().1, remarks);
+        Ok(())
+    }
+}
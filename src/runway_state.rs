@@ -0,0 +1,581 @@
+use std::fmt;
+use std::str::FromStr;
+
+use nom::{
+    branch::alt,
+    bytes::complete::{tag, take},
+    combinator::{map, map_opt},
+    multi::many0,
+    sequence::{pair, preceded, separated_pair, terminated, tuple},
+    IResult,
+};
+
+use crate::error::{MetarElement, MetarError};
+use crate::rvr::{parse_runway, Runway, RunwayPosition};
+use crate::{code, decode, end_of_group, Code};
+
+#[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(
+    feature = "serde",
+    serde(tag = "type", content = "value", rename_all = "snake_case")
+)]
+pub enum RunwayStateDesignator {
+    Runway(Runway),
+    /// Code 88: the report applies to all runways.
+    AllRunways,
+    /// Code 99: repetition of the previous report, no new information.
+    Repeated,
+}
+
+impl From<Runway> for RunwayStateDesignator {
+    fn from(runway: Runway) -> Self {
+        match runway.number {
+            88 => RunwayStateDesignator::AllRunways,
+            99 => RunwayStateDesignator::Repeated,
+            _ => RunwayStateDesignator::Runway(runway),
+        }
+    }
+}
+
+impl Code for RunwayStateDesignator {
+    const ELEMENT: MetarElement = MetarElement::RunwayState;
+    const EXPECTED: &'static str = "runway number or MOTNE code";
+
+    /// Parses the two-digit MOTNE designator, where 50 is added to the
+    /// number of right-hand parallel runways.
+    fn from_code(s: &str) -> Option<Self> {
+        match s.parse::<i8>().ok() {
+            Some(88) => Some(RunwayStateDesignator::AllRunways),
+            Some(99) => Some(RunwayStateDesignator::Repeated),
+            Some(n @ 1..=36) => Some(RunwayStateDesignator::Runway(Runway {
+                number: n,
+                position: None,
+            })),
+            Some(n @ 51..=86) => Some(RunwayStateDesignator::Runway(Runway {
+                number: n - 50,
+                position: Some(RunwayPosition::Right),
+            })),
+            _ => None,
+        }
+    }
+}
+
+impl FromStr for RunwayStateDesignator {
+    type Err = MetarError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        decode(s)
+    }
+}
+
+#[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum DepositType {
+    ClearAndDry,
+    Damp,
+    Wet,
+    Frost,
+    DrySnow,
+    WetSnow,
+    Slush,
+    Ice,
+    CompactedSnow,
+    FrozenRuts,
+}
+
+impl Code for DepositType {
+    const ELEMENT: MetarElement = MetarElement::RunwayState;
+    const EXPECTED: &'static str = "deposit code 0-9";
+
+    fn from_code(s: &str) -> Option<Self> {
+        match s {
+            "0" => Some(DepositType::ClearAndDry),
+            "1" => Some(DepositType::Damp),
+            "2" => Some(DepositType::Wet),
+            "3" => Some(DepositType::Frost),
+            "4" => Some(DepositType::DrySnow),
+            "5" => Some(DepositType::WetSnow),
+            "6" => Some(DepositType::Slush),
+            "7" => Some(DepositType::Ice),
+            "8" => Some(DepositType::CompactedSnow),
+            "9" => Some(DepositType::FrozenRuts),
+            _ => None,
+        }
+    }
+}
+
+impl FromStr for DepositType {
+    type Err = MetarError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        decode(s)
+    }
+}
+
+#[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum ContaminationExtent {
+    UpTo10Percent,
+    UpTo25Percent,
+    UpTo50Percent,
+    UpTo100Percent,
+}
+
+impl Code for ContaminationExtent {
+    const ELEMENT: MetarElement = MetarElement::RunwayState;
+    const EXPECTED: &'static str = "contamination code 1, 2, 5 or 9";
+
+    fn from_code(s: &str) -> Option<Self> {
+        match s {
+            "1" => Some(ContaminationExtent::UpTo10Percent),
+            "2" => Some(ContaminationExtent::UpTo25Percent),
+            "5" => Some(ContaminationExtent::UpTo50Percent),
+            "9" => Some(ContaminationExtent::UpTo100Percent),
+            _ => None,
+        }
+    }
+}
+
+impl FromStr for ContaminationExtent {
+    type Err = MetarError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        decode(s)
+    }
+}
+
+#[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(
+    feature = "serde",
+    serde(tag = "type", content = "value", rename_all = "snake_case")
+)]
+pub enum DepositDepth {
+    /// Depth in millimetres; `0` means less than 1 mm and `400` means
+    /// 40 cm or more.
+    Millimetres(u16),
+    /// Code 99: runway not operational due to snow, slush, ice or clearance.
+    NotOperational,
+}
+
+impl Code for DepositDepth {
+    const ELEMENT: MetarElement = MetarElement::RunwayState;
+    const EXPECTED: &'static str = "deposit depth code";
+
+    fn from_code(s: &str) -> Option<Self> {
+        match s.parse::<u16>().ok() {
+            Some(n @ 0..=90) => Some(DepositDepth::Millimetres(n)),
+            Some(n @ 92..=98) => Some(DepositDepth::Millimetres((n - 90) * 50)),
+            Some(99) => Some(DepositDepth::NotOperational),
+            _ => None,
+        }
+    }
+}
+
+impl FromStr for DepositDepth {
+    type Err = MetarError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        decode(s)
+    }
+}
+
+#[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(
+    feature = "serde",
+    serde(tag = "type", content = "value", rename_all = "snake_case")
+)]
+pub enum BrakingAction {
+    /// Measured friction coefficient, in hundredths.
+    Friction(u8),
+    Poor,
+    MediumPoor,
+    Medium,
+    MediumGood,
+    Good,
+    Unreliable,
+}
+
+impl BrakingAction {
+    pub fn friction_coefficient(&self) -> Option<f64> {
+        match self {
+            BrakingAction::Friction(f) => Some(*f as f64 / 100.0),
+            _ => None,
+        }
+    }
+}
+
+impl Code for BrakingAction {
+    const ELEMENT: MetarElement = MetarElement::RunwayState;
+    const EXPECTED: &'static str = "braking action code";
+
+    fn from_code(s: &str) -> Option<Self> {
+        match s.parse::<u8>().ok() {
+            Some(n @ 0..=90) => Some(BrakingAction::Friction(n)),
+            Some(91) => Some(BrakingAction::Poor),
+            Some(92) => Some(BrakingAction::MediumPoor),
+            Some(93) => Some(BrakingAction::Medium),
+            Some(94) => Some(BrakingAction::MediumGood),
+            Some(95) => Some(BrakingAction::Good),
+            Some(99) => Some(BrakingAction::Unreliable),
+            _ => None,
+        }
+    }
+}
+
+impl FromStr for BrakingAction {
+    type Err = MetarError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        decode(s)
+    }
+}
+
+/// `None` fields stand for elements reported as `/`.
+#[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(
+    feature = "serde",
+    serde(tag = "type", content = "value", rename_all = "snake_case")
+)]
+pub enum RunwayCondition {
+    Contaminated {
+        deposit: Option<DepositType>,
+        extent: Option<ContaminationExtent>,
+        depth: Option<DepositDepth>,
+        braking: Option<BrakingAction>,
+    },
+    /// `CLRD`: contamination has ceased to exist.
+    Cleared { braking: Option<BrakingAction> },
+    /// `SNOCLO`: aerodrome closed due to snow.
+    SnowClosed,
+}
+
+#[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct RunwayState {
+    pub runway: RunwayStateDesignator,
+    pub condition: RunwayCondition,
+}
+
+/// Writes the runway as in the R-format, e.g. `26R` for MOTNE `76`, or `88`
+/// and `99` for all runways and a repeated report.
+impl fmt::Display for RunwayStateDesignator {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RunwayStateDesignator::Runway(runway) => write!(f, "{}", runway),
+            RunwayStateDesignator::AllRunways => f.write_str("88"),
+            RunwayStateDesignator::Repeated => f.write_str("99"),
+        }
+    }
+}
+
+impl fmt::Display for DepositType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let code = match self {
+            DepositType::ClearAndDry => 0,
+            DepositType::Damp => 1,
+            DepositType::Wet => 2,
+            DepositType::Frost => 3,
+            DepositType::DrySnow => 4,
+            DepositType::WetSnow => 5,
+            DepositType::Slush => 6,
+            DepositType::Ice => 7,
+            DepositType::CompactedSnow => 8,
+            DepositType::FrozenRuts => 9,
+        };
+        write!(f, "{}", code)
+    }
+}
+
+impl fmt::Display for ContaminationExtent {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let code = match self {
+            ContaminationExtent::UpTo10Percent => 1,
+            ContaminationExtent::UpTo25Percent => 2,
+            ContaminationExtent::UpTo50Percent => 5,
+            ContaminationExtent::UpTo100Percent => 9,
+        };
+        write!(f, "{}", code)
+    }
+}
+
+/// Depths above 90 mm are written in steps of 5 cm, codes 92 to 98.
+impl fmt::Display for DepositDepth {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DepositDepth::Millimetres(mm @ 0..=90) => write!(f, "{:02}", mm),
+            DepositDepth::Millimetres(mm) => write!(f, "{}", 90 + (mm / 50).clamp(2, 8)),
+            DepositDepth::NotOperational => f.write_str("99"),
+        }
+    }
+}
+
+impl fmt::Display for BrakingAction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let code = match self {
+            BrakingAction::Friction(friction) => *friction,
+            BrakingAction::Poor => 91,
+            BrakingAction::MediumPoor => 92,
+            BrakingAction::Medium => 93,
+            BrakingAction::MediumGood => 94,
+            BrakingAction::Good => 95,
+            BrakingAction::Unreliable => 99,
+        };
+        write!(f, "{:02}", code)
+    }
+}
+
+/// Writes `code`, or `width` slashes when it is `None`.
+fn fmt_optional_code<T: fmt::Display>(
+    code: &Option<T>,
+    width: usize,
+    f: &mut fmt::Formatter<'_>,
+) -> fmt::Result {
+    match code {
+        Some(code) => write!(f, "{}", code),
+        None => write!(f, "{:/<width$}", ""),
+    }
+}
+
+impl fmt::Display for RunwayCondition {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RunwayCondition::Contaminated {
+                deposit,
+                extent,
+                depth,
+                braking,
+            } => {
+                fmt_optional_code(deposit, 1, f)?;
+                fmt_optional_code(extent, 1, f)?;
+                fmt_optional_code(depth, 2, f)?;
+                fmt_optional_code(braking, 2, f)
+            }
+            RunwayCondition::Cleared { braking } => {
+                f.write_str("CLRD")?;
+                fmt_optional_code(braking, 2, f)
+            }
+            RunwayCondition::SnowClosed => f.write_str("SNOCLO"),
+        }
+    }
+}
+
+/// Writes the `R24/451293` format, or `R/SNOCLO` when the aerodrome is
+/// closed.
+impl fmt::Display for RunwayState {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match (&self.runway, &self.condition) {
+            (RunwayStateDesignator::AllRunways, RunwayCondition::SnowClosed) => {
+                f.write_str("R/SNOCLO")
+            }
+            (runway, condition) => write!(f, "R{}/{}", runway, condition),
+        }
+    }
+}
+
+/// Takes `n` characters and parses them, yielding `None` when they are all
+/// slashes.
+fn optional_code<T: Code>(n: usize) -> impl FnMut(&str) -> IResult<&str, Option<T>> {
+    move |s: &str| {
+        map_opt(take(n), |digits: &str| {
+            if digits.chars().all(|c| c == '/') {
+                Some(None)
+            } else {
+                code(digits).map(Some)
+            }
+        })(s)
+    }
+}
+
+fn parse_condition(s: &str) -> IResult<&str, RunwayCondition> {
+    alt((
+        map(tag("SNOCLO"), |_| RunwayCondition::SnowClosed),
+        map(preceded(tag("CLRD"), optional_code(2)), |braking| {
+            RunwayCondition::Cleared { braking }
+        }),
+        map(
+            tuple((
+                optional_code(1),
+                optional_code(1),
+                optional_code(2),
+                optional_code(2),
+            )),
+            |(deposit, extent, depth, braking)| RunwayCondition::Contaminated {
+                deposit,
+                extent,
+                depth,
+                braking,
+            },
+        ),
+    ))(s)
+}
+
+pub fn parse_runway_state(s: &str) -> IResult<&str, RunwayState> {
+    let s = s.trim_start();
+    let (rest, (runway, condition)) = terminated(
+        alt((
+            map(alt((tag("R/SNOCLO"), tag("SNOCLO"))), |_| {
+                (
+                    RunwayStateDesignator::AllRunways,
+                    RunwayCondition::SnowClosed,
+                )
+            }),
+            separated_pair(
+                map(
+                    preceded(tag("R"), parse_runway),
+                    RunwayStateDesignator::from,
+                ),
+                tag("/"),
+                parse_condition,
+            ),
+            pair(
+                map_opt(take(2usize), code::<RunwayStateDesignator>),
+                parse_condition,
+            ),
+        )),
+        end_of_group,
+    )(s)?;
+
+    Ok((rest, RunwayState { runway, condition }))
+}
+
+pub fn parse_runway_states(s: &str) -> IResult<&str, Vec<RunwayState>> {
+    many0(parse_runway_state)(s)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_parse_motne_runway_state() -> anyhow::Result<()> {
+        assert_eq!(
+            parse_runway_state("8849//91")?.1,
+            RunwayState {
+                runway: RunwayStateDesignator::AllRunways,
+                condition: RunwayCondition::Contaminated {
+                    deposit: Some(DepositType::DrySnow),
+                    extent: Some(ContaminationExtent::UpTo100Percent),
+                    depth: None,
+                    braking: Some(BrakingAction::Poor)
+                }
+            }
+        );
+        assert_eq!(
+            parse_runway_state("76290235")?.1,
+            RunwayState {
+                runway: RunwayStateDesignator::Runway(Runway {
+                    number: 26,
+                    position: Some(RunwayPosition::Right)
+                }),
+                condition: RunwayCondition::Contaminated {
+                    deposit: Some(DepositType::Wet),
+                    extent: Some(ContaminationExtent::UpTo100Percent),
+                    depth: Some(DepositDepth::Millimetres(2)),
+                    braking: Some(BrakingAction::Friction(35))
+                }
+            }
+        );
+        assert_eq!(
+            parse_runway_state("99421594")?.1.runway,
+            RunwayStateDesignator::Repeated
+        );
+        assert!(parse_runway_state("1234").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_r_format_runway_state() -> anyhow::Result<()> {
+        assert_eq!(
+            parse_runway_state("R24/451293")?.1,
+            RunwayState {
+                runway: RunwayStateDesignator::Runway(Runway {
+                    number: 24,
+                    position: None
+                }),
+                condition: RunwayCondition::Contaminated {
+                    deposit: Some(DepositType::DrySnow),
+                    extent: Some(ContaminationExtent::UpTo50Percent),
+                    depth: Some(DepositDepth::Millimetres(12)),
+                    braking: Some(BrakingAction::Medium)
+                }
+            }
+        );
+        assert_eq!(
+            parse_runway_state("R27L/7/9895")?.1.condition,
+            RunwayCondition::Contaminated {
+                deposit: Some(DepositType::Ice),
+                extent: None,
+                depth: Some(DepositDepth::Millimetres(400)),
+                braking: Some(BrakingAction::Good)
+            }
+        );
+        assert_eq!(
+            parse_runway_state("R14/CLRD//")?.1.condition,
+            RunwayCondition::Cleared { braking: None }
+        );
+        assert_eq!(
+            parse_runway_state("R88/SNOCLO")?.1,
+            RunwayState {
+                runway: RunwayStateDesignator::AllRunways,
+                condition: RunwayCondition::SnowClosed
+            }
+        );
+        assert_eq!(
+            parse_runway_state("R/SNOCLO")?.1.condition,
+            RunwayCondition::SnowClosed
+        );
+        assert_eq!(
+            parse_runway_state("R06/459999")?.1.condition,
+            RunwayCondition::Contaminated {
+                deposit: Some(DepositType::DrySnow),
+                extent: Some(ContaminationExtent::UpTo50Percent),
+                depth: Some(DepositDepth::NotOperational),
+                braking: Some(BrakingAction::Unreliable)
+            }
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_runway_states() -> anyhow::Result<()> {
+        let (rest, states) = parse_runway_states(" R24/451293 88CLRD95 NOSIG")?;
+        assert_eq!(rest, " NOSIG");
+        assert_eq!(states.len(), 2);
+        assert_eq!(
+            states[1].condition,
+            RunwayCondition::Cleared {
+                braking: Some(BrakingAction::Good)
+            }
+        );
+        assert_eq!(
+            BrakingAction::Friction(35).friction_coefficient(),
+            Some(0.35)
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_display_runway_state() -> anyhow::Result<()> {
+        for (sample, expected) in [
+            ("R24/451293", "R24/451293"),
+            ("R27L/7/9895", "R27L/7/9895"),
+            ("R14/CLRD//", "R14/CLRD//"),
+            ("R/SNOCLO", "R/SNOCLO"),
+            ("R06/459999", "R06/459999"),
+            ("8849//91", "R88/49//91"),
+            ("76290235", "R26R/290235"),
+            ("99421594", "R99/421594"),
+        ] {
+            let state = parse_runway_state(sample)?.1;
+            assert_eq!(state.to_string(), expected);
+            assert_eq!(parse_runway_state(expected)?.1, state);
+        }
+        Ok(())
+    }
+}
@@ -1,118 +1,309 @@
+use std::fmt;
 use std::str::FromStr;
 
 use nom::{
     branch::alt,
-    bytes::complete::tag,
-    character::complete::{i32 as nomi32, i8 as nomi8},
-    combinator::{map_opt, opt},
+    bytes::complete::{tag, take_while_m_n},
+    character::complete::{i8 as nomi8, multispace1},
+    combinator::{map, map_opt, map_res, opt},
     multi::separated_list0,
-    sequence::tuple,
+    sequence::{pair, preceded, terminated, tuple},
     IResult,
 };
 
-#[derive(Debug, PartialEq)]
+use crate::error::{MetarElement, MetarError};
+use crate::{code, decode, end_of_group, Code};
+
+#[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
 pub enum RunwayPosition {
     Left,
     Center,
     Right,
 }
 
-impl FromStr for RunwayPosition {
-    type Err = String;
+impl Code for RunwayPosition {
+    const ELEMENT: MetarElement = MetarElement::Rvr;
+    const EXPECTED: &'static str = "L, C or R";
 
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
+    fn from_code(s: &str) -> Option<Self> {
         match s {
-            "L" => Ok(RunwayPosition::Left),
-            "R" => Ok(RunwayPosition::Right),
-            "C" => Ok(RunwayPosition::Center),
-            _ => Err(format!("Cannot parse {}", s)),
+            "L" => Some(RunwayPosition::Left),
+            "R" => Some(RunwayPosition::Right),
+            "C" => Some(RunwayPosition::Center),
+            _ => None,
         }
     }
 }
 
-#[derive(Debug, PartialEq)]
+impl FromStr for RunwayPosition {
+    type Err = MetarError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        decode(s)
+    }
+}
+
+#[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
 pub enum VisibilityScale {
     Plus,
     Minus,
 }
 
-impl FromStr for VisibilityScale {
-    type Err = String;
+impl Code for VisibilityScale {
+    const ELEMENT: MetarElement = MetarElement::Rvr;
+    const EXPECTED: &'static str = "P or M";
 
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
+    fn from_code(s: &str) -> Option<Self> {
         match s {
-            "P" => Ok(VisibilityScale::Plus),
-            "M" => Ok(VisibilityScale::Minus),
-            _ => Err(format!("Cannot parse {}", s)),
+            "P" => Some(VisibilityScale::Plus),
+            "M" => Some(VisibilityScale::Minus),
+            _ => None,
         }
     }
 }
-#[derive(Debug, PartialEq)]
+
+impl FromStr for VisibilityScale {
+    type Err = MetarError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        decode(s)
+    }
+}
+#[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
 pub enum VisibilityStatus {
     Down,
     Up,
     No,
 }
+impl Code for VisibilityStatus {
+    const ELEMENT: MetarElement = MetarElement::Rvr;
+    const EXPECTED: &'static str = "D, U or N";
+
+    fn from_code(s: &str) -> Option<Self> {
+        match s {
+            "D" => Some(VisibilityStatus::Down),
+            "U" => Some(VisibilityStatus::Up),
+            "N" => Some(VisibilityStatus::No),
+            _ => None,
+        }
+    }
+}
+
 impl FromStr for VisibilityStatus {
-    type Err = String;
+    type Err = MetarError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        match s {
-            "D" => Ok(VisibilityStatus::Down),
-            "U" => Ok(VisibilityStatus::Up),
-            "N" => Ok(VisibilityStatus::No),
-            _ => Err(format!("Cannot parse {}", s)),
+        decode(s)
+    }
+}
+
+/// Runway designator as used outside of RVR groups, e.g. `27L` in `WS R27L`.
+#[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Runway {
+    pub number: i8,
+    pub position: Option<RunwayPosition>,
+}
+
+fn parse_runway_position(s: &str) -> IResult<&str, RunwayPosition> {
+    map_opt(alt((tag("L"), tag("R"), tag("C"))), code)(s)
+}
+
+pub fn parse_runway(s: &str) -> IResult<&str, Runway> {
+    let (rest, (number, position)) = tuple((nomi8, opt(parse_runway_position)))(s)?;
+    Ok((rest, Runway { number, position }))
+}
+
+/// Unit of a runway visual range value: meters, or feet when the group ends
+/// with `FT`.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum RvrUnit {
+    Meters,
+    Feet,
+}
+
+#[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct RvrDistance {
+    pub value: u16,
+    pub unit: RvrUnit,
+    /// `P`/`M`: the range is above or below the measurable limit.
+    pub scale: Option<VisibilityScale>,
+}
+
+impl RvrDistance {
+    pub fn meters(&self) -> f64 {
+        match self.unit {
+            RvrUnit::Meters => self.value as f64,
+            RvrUnit::Feet => self.value as f64 * 0.3048,
+        }
+    }
+
+    pub fn feet(&self) -> f64 {
+        match self.unit {
+            RvrUnit::Meters => self.value as f64 / 0.3048,
+            RvrUnit::Feet => self.value as f64,
         }
     }
 }
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct RunwayVisualRange {
     pub number: i8,
     pub position: Option<RunwayPosition>,
-    pub visibility_meters: i32,
-    pub visibility_scale: Option<VisibilityScale>,
+    /// `None` when the value is missing, e.g. `R24/////`.
+    pub visibility: Option<RvrDistance>,
+    /// Upper bound of a variable range, e.g. `1600` in `R24/1000V1600FT`.
+    pub variable_visibility: Option<RvrDistance>,
     pub visibility_status: Option<VisibilityStatus>,
 }
 
+impl fmt::Display for RunwayPosition {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let position = match self {
+            RunwayPosition::Left => "L",
+            RunwayPosition::Center => "C",
+            RunwayPosition::Right => "R",
+        };
+        f.write_str(position)
+    }
+}
+
+impl fmt::Display for VisibilityScale {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            VisibilityScale::Plus => f.write_str("P"),
+            VisibilityScale::Minus => f.write_str("M"),
+        }
+    }
+}
+
+impl fmt::Display for VisibilityStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let status = match self {
+            VisibilityStatus::Down => "D",
+            VisibilityStatus::Up => "U",
+            VisibilityStatus::No => "N",
+        };
+        f.write_str(status)
+    }
+}
+
+impl fmt::Display for Runway {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:02}", self.number)?;
+        if let Some(position) = &self.position {
+            write!(f, "{}", position)?;
+        }
+        Ok(())
+    }
+}
+
+/// Writes the scale and the value only: the unit is written once for the
+/// whole group, by [`RunwayVisualRange`].
+impl fmt::Display for RvrDistance {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(scale) = &self.scale {
+            write!(f, "{}", scale)?;
+        }
+        write!(f, "{:04}", self.value)
+    }
+}
+
+impl fmt::Display for RunwayVisualRange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let runway = Runway {
+            number: self.number,
+            position: self.position.clone(),
+        };
+        write!(f, "R{}/", runway)?;
+        match &self.visibility {
+            Some(visibility) => {
+                write!(f, "{}", visibility)?;
+                if let Some(variable) = &self.variable_visibility {
+                    write!(f, "V{}", variable)?;
+                }
+                if visibility.unit == RvrUnit::Feet {
+                    f.write_str("FT")?;
+                }
+            }
+            None => f.write_str("////")?,
+        }
+        if let Some(status) = &self.visibility_status {
+            write!(f, "{}", status)?;
+        }
+        Ok(())
+    }
+}
+
+fn parse_rvr_value(s: &str) -> IResult<&str, (Option<VisibilityScale>, u16)> {
+    let scale_parser = map_opt(alt((tag("M"), tag("P"))), code);
+    tuple((
+        opt(scale_parser),
+        map_res(
+            take_while_m_n(1, 4, |c: char| c.is_ascii_digit()),
+            str::parse,
+        ),
+    ))(s)
+}
+
 pub fn parse_rvr(s: &str) -> IResult<&str, RunwayVisualRange> {
-    let meter_parser = nomi32;
-    let position_parser = map_opt(
-        opt(alt((tag("L"), tag("R"), tag("C")))),
-        |s: Option<&str>| s.and_then(|x| x.parse::<RunwayPosition>().ok()),
-    );
-
-    let vis_scale_parser = map_opt(opt(alt((tag("M"), tag("P")))), |s: Option<&str>| {
-        s.and_then(|x| x.parse::<VisibilityScale>().ok())
-    });
-
-    let vis_status_parser = map_opt(
-        opt(alt((tag("D"), tag("U"), tag("N")))),
-        |s: Option<&str>| s.and_then(|x| x.parse::<VisibilityStatus>().ok()),
-    );
-
-    let (other, (_, number, position, _, vis_scale, vis_meter, vis_status)) = tuple((
-        tag("R"),
-        nomi8,
-        opt(position_parser),
-        tag("/"),
-        opt(vis_scale_parser),
-        meter_parser,
-        opt(vis_status_parser),
-    ))(s)?;
+    let position_parser = map_opt(alt((tag("L"), tag("R"), tag("C"))), code);
+    let vis_status_parser = map_opt(alt((tag("D"), tag("U"), tag("N"))), code);
+
+    let (other, (_, number, position, _, values, feet, vis_status)) = terminated(
+        tuple((
+            tag("R"),
+            nomi8,
+            opt(position_parser),
+            tag("/"),
+            alt((
+                map(tag("////"), |_| None),
+                map(
+                    pair(parse_rvr_value, opt(preceded(tag("V"), parse_rvr_value))),
+                    Some,
+                ),
+            )),
+            opt(tag("FT")),
+            opt(vis_status_parser),
+        )),
+        end_of_group,
+    )(s)?;
+
+    let unit = if feet.is_some() {
+        RvrUnit::Feet
+    } else {
+        RvrUnit::Meters
+    };
+    let distance = |(scale, value)| RvrDistance { value, unit, scale };
+    let (visibility, variable_visibility) = match values {
+        Some((low, high)) => (Some(distance(low)), high.map(distance)),
+        None => (None, None),
+    };
     Ok((
         other,
         RunwayVisualRange {
             number,
             position,
-            visibility_meters: vis_meter,
-            visibility_scale: vis_scale,
+            visibility,
+            variable_visibility,
             visibility_status: vis_status,
         },
     ))
 }
+
 pub fn parse_rvrs(s: &str) -> IResult<&str, Vec<RunwayVisualRange>> {
-    separated_list0(tag(" "), parse_rvr)(s)
+    let s = s.trim_start();
+    separated_list0(multispace1, parse_rvr)(s)
 }
 
 #[cfg(test)]
@@ -126,8 +317,12 @@ mod test {
             RunwayVisualRange {
                 number: 25,
                 position: None,
-                visibility_meters: 75,
-                visibility_scale: Some(VisibilityScale::Minus),
+                visibility: Some(RvrDistance {
+                    value: 75,
+                    unit: RvrUnit::Meters,
+                    scale: Some(VisibilityScale::Minus)
+                }),
+                variable_visibility: None,
                 visibility_status: Some(VisibilityStatus::Up)
             }
         );
@@ -141,14 +336,94 @@ mod test {
             RunwayVisualRange {
                 number: 25,
                 position: Some(RunwayPosition::Left),
-                visibility_meters: 1075,
-                visibility_scale: Some(VisibilityScale::Plus),
+                visibility: Some(RvrDistance {
+                    value: 1075,
+                    unit: RvrUnit::Meters,
+                    scale: Some(VisibilityScale::Plus)
+                }),
+                variable_visibility: None,
                 visibility_status: Some(VisibilityStatus::No)
             }
         );
         Ok(())
     }
 
+    #[test]
+    fn test_parse_rvr_feet_variable_missing() -> anyhow::Result<()> {
+        let res = parse_rvr("R24/1200FT")?.1;
+        assert_eq!(
+            res.visibility,
+            Some(RvrDistance {
+                value: 1200,
+                unit: RvrUnit::Feet,
+                scale: None
+            })
+        );
+        assert!((res.visibility.unwrap().meters() - 365.76).abs() < 1e-9);
+
+        let res = parse_rvr("R24/1000V1600FT")?.1;
+        assert_eq!(
+            res.visibility.map(|d| (d.value, d.unit)),
+            Some((1000, RvrUnit::Feet))
+        );
+        assert_eq!(
+            res.variable_visibility,
+            Some(RvrDistance {
+                value: 1600,
+                unit: RvrUnit::Feet,
+                scale: None
+            })
+        );
+
+        let res = parse_rvr("R06/M0600V1200U")?.1;
+        assert_eq!(
+            res,
+            RunwayVisualRange {
+                number: 6,
+                position: None,
+                visibility: Some(RvrDistance {
+                    value: 600,
+                    unit: RvrUnit::Meters,
+                    scale: Some(VisibilityScale::Minus)
+                }),
+                variable_visibility: Some(RvrDistance {
+                    value: 1200,
+                    unit: RvrUnit::Meters,
+                    scale: None
+                }),
+                visibility_status: Some(VisibilityStatus::Up)
+            }
+        );
+
+        let res = parse_rvr("R24/////")?.1;
+        assert_eq!(res.number, 24);
+        assert_eq!(res.visibility, None);
+        assert_eq!(res.variable_visibility, None);
+
+        assert!(parse_rvr("R24/12000").is_err());
+        assert!(parse_rvr("R24/1200X").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_runway() -> anyhow::Result<()> {
+        assert_eq!(
+            parse_runway("27L")?.1,
+            Runway {
+                number: 27,
+                position: Some(RunwayPosition::Left)
+            }
+        );
+        assert_eq!(
+            parse_runway("06")?.1,
+            Runway {
+                number: 6,
+                position: None
+            }
+        );
+        Ok(())
+    }
+
     #[test]
     fn test_parse_rvrs() -> anyhow::Result<()> {
         let res = parse_rvrs("R25L/M1075N R25C/P200U")?.1;
@@ -158,19 +433,42 @@ mod test {
                 RunwayVisualRange {
                     number: 25,
                     position: Some(RunwayPosition::Left),
-                    visibility_meters: 1075,
-                    visibility_scale: Some(VisibilityScale::Minus),
+                    visibility: Some(RvrDistance {
+                        value: 1075,
+                        unit: RvrUnit::Meters,
+                        scale: Some(VisibilityScale::Minus)
+                    }),
+                    variable_visibility: None,
                     visibility_status: Some(VisibilityStatus::No)
                 },
                 RunwayVisualRange {
                     number: 25,
                     position: Some(RunwayPosition::Center),
-                    visibility_meters: 200,
-                    visibility_scale: Some(VisibilityScale::Plus),
+                    visibility: Some(RvrDistance {
+                        value: 200,
+                        unit: RvrUnit::Meters,
+                        scale: Some(VisibilityScale::Plus)
+                    }),
+                    variable_visibility: None,
                     visibility_status: Some(VisibilityStatus::Up)
                 }
             ]
         );
         Ok(())
     }
+
+    #[test]
+    fn test_display_rvr() -> anyhow::Result<()> {
+        for sample in [
+            "R25/M0075U",
+            "R04R/1000V1600FT",
+            "R22/P1500U",
+            "R06L/0600N",
+            "R24/////",
+            "R08C/M0050VP2000D",
+        ] {
+            assert_eq!(parse_rvr(sample)?.1.to_string(), sample);
+        }
+        Ok(())
+    }
 }
@@ -0,0 +1,185 @@
+use std::fmt;
+
+use nom::{
+    branch::alt,
+    bytes::complete::{tag, take_while1, take_while_m_n},
+    combinator::{map, map_res, value},
+    sequence::{preceded, separated_pair, terminated},
+    IResult,
+};
+
+use crate::end_of_group;
+use crate::temperature::{fmt_degrees, parse_degrees, Degrees};
+
+/// Second half of a sea group. `None` stands for a value reported as `/`.
+#[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(
+    feature = "serde",
+    serde(tag = "type", content = "value", rename_all = "snake_case")
+)]
+pub enum SeaCondition {
+    /// State of the sea, WMO code 0 (calm, glassy) to 9 (phenomenal).
+    State(Option<u8>),
+    /// Significant wave height in decimetres.
+    WaveHeight(Option<u16>),
+}
+
+/// Sea surface temperature and state of the sea, e.g. `W15/S4` or `W12/H75`.
+#[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SeaState {
+    pub temperature: Option<Degrees>,
+    pub condition: SeaCondition,
+}
+
+impl SeaState {
+    /// Significant wave height in metres.
+    pub fn wave_height_meters(&self) -> Option<f64> {
+        match self.condition {
+            SeaCondition::WaveHeight(Some(h)) => Some(h as f64 / 10.0),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for SeaCondition {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SeaCondition::State(Some(state)) => write!(f, "S{}", state),
+            SeaCondition::State(None) => f.write_str("S/"),
+            SeaCondition::WaveHeight(Some(height)) => write!(f, "H{}", height),
+            SeaCondition::WaveHeight(None) => f.write_str("H///"),
+        }
+    }
+}
+
+impl fmt::Display for SeaState {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("W")?;
+        fmt_degrees(self.temperature, f)?;
+        write!(f, "/{}", self.condition)
+    }
+}
+
+fn missing(s: &str) -> IResult<&str, &str> {
+    take_while1(|c: char| c == '/')(s)
+}
+
+fn parse_state(s: &str) -> IResult<&str, Option<u8>> {
+    alt((
+        map(
+            map_res(
+                take_while_m_n(1, 1, |c: char| c.is_ascii_digit()),
+                str::parse,
+            ),
+            Some,
+        ),
+        value(None, missing),
+    ))(s)
+}
+
+fn parse_wave_height(s: &str) -> IResult<&str, Option<u16>> {
+    alt((
+        map(
+            map_res(
+                take_while_m_n(1, 3, |c: char| c.is_ascii_digit()),
+                str::parse,
+            ),
+            Some,
+        ),
+        value(None, missing),
+    ))(s)
+}
+
+fn parse_condition(s: &str) -> IResult<&str, SeaCondition> {
+    alt((
+        map(preceded(tag("S"), parse_state), SeaCondition::State),
+        map(
+            preceded(tag("H"), parse_wave_height),
+            SeaCondition::WaveHeight,
+        ),
+    ))(s)
+}
+
+pub fn parse_sea_state(s: &str) -> IResult<&str, SeaState> {
+    let s = s.trim_start();
+    let (rest, (temperature, condition)) = terminated(
+        preceded(
+            tag("W"),
+            separated_pair(parse_degrees, tag("/"), parse_condition),
+        ),
+        end_of_group,
+    )(s)?;
+
+    Ok((
+        rest,
+        SeaState {
+            temperature,
+            condition,
+        },
+    ))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_parse_sea_state() -> anyhow::Result<()> {
+        assert_eq!(
+            parse_sea_state("W15/S4")?.1,
+            SeaState {
+                temperature: Some(Degrees::new(15)),
+                condition: SeaCondition::State(Some(4))
+            }
+        );
+        assert_eq!(
+            parse_sea_state("W12/H75")?.1,
+            SeaState {
+                temperature: Some(Degrees::new(12)),
+                condition: SeaCondition::WaveHeight(Some(75))
+            }
+        );
+        assert_eq!(
+            parse_sea_state("W12/H75")?.1.wave_height_meters(),
+            Some(7.5)
+        );
+        assert_eq!(
+            parse_sea_state("WM01/H125")?.1,
+            SeaState {
+                temperature: Some(Degrees::new(-1)),
+                condition: SeaCondition::WaveHeight(Some(125))
+            }
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_sea_state_missing() -> anyhow::Result<()> {
+        assert_eq!(
+            parse_sea_state("W///S/")?.1,
+            SeaState {
+                temperature: None,
+                condition: SeaCondition::State(None)
+            }
+        );
+        assert_eq!(
+            parse_sea_state("W08/H///")?.1,
+            SeaState {
+                temperature: Some(Degrees::new(8)),
+                condition: SeaCondition::WaveHeight(None)
+            }
+        );
+        assert!(parse_sea_state("WS R27L").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_display_sea_state() -> anyhow::Result<()> {
+        for sample in ["W15/S4", "W12/H75", "WM01/S/", "WM00/S2", "W///H///"] {
+            assert_eq!(parse_sea_state(sample)?.1.to_string(), sample);
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,181 @@
+use std::fmt;
+use std::str::FromStr;
+
+use nom::{
+    branch::alt,
+    bytes::complete::tag,
+    character::complete::multispace1,
+    combinator::{map, map_opt, opt},
+    sequence::{pair, preceded},
+    IResult,
+};
+
+use crate::error::{MetarElement, MetarError};
+use crate::rvr::{parse_runway, Runway};
+use crate::{code, decode, Code, GroupWriter, WriteGroups};
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum SensorKind {
+    /// `RVRNO`
+    Rvr,
+    /// `PWINO`: present weather identifier.
+    PresentWeather,
+    /// `PNO`: precipitation amount (tipping bucket).
+    Precipitation,
+    /// `FZRANO`: freezing rain sensor.
+    FreezingRain,
+    /// `TSNO`: lightning detection.
+    Lightning,
+    /// `VISNO`: visibility at a secondary location.
+    Visibility,
+    /// `CHINO`: cloud height indicator at a secondary location.
+    Ceiling,
+}
+
+impl Code for SensorKind {
+    const ELEMENT: MetarElement = MetarElement::Remarks;
+    const EXPECTED: &'static str = "sensor outage code such as PWINO";
+
+    fn from_code(s: &str) -> Option<Self> {
+        match s {
+            "RVRNO" => Some(SensorKind::Rvr),
+            "PWINO" => Some(SensorKind::PresentWeather),
+            "PNO" => Some(SensorKind::Precipitation),
+            "FZRANO" => Some(SensorKind::FreezingRain),
+            "TSNO" => Some(SensorKind::Lightning),
+            "VISNO" => Some(SensorKind::Visibility),
+            "CHINO" => Some(SensorKind::Ceiling),
+            _ => None,
+        }
+    }
+}
+
+impl FromStr for SensorKind {
+    type Err = MetarError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        decode(s)
+    }
+}
+
+#[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SensorOutage {
+    pub sensor: SensorKind,
+    /// Runway of a secondary-location sensor, e.g. `VISNO RWY06`.
+    pub runway: Option<Runway>,
+}
+
+/// Sensors reported as out of service, and the `$` maintenance indicator.
+#[derive(Debug, PartialEq, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SensorStatus {
+    pub outages: Vec<SensorOutage>,
+    pub maintenance_required: bool,
+}
+
+impl SensorStatus {
+    pub fn is_out(&self, sensor: SensorKind) -> bool {
+        self.outages.iter().any(|o| o.sensor == sensor)
+    }
+}
+
+/// Whether an element is in the report, and if not, why.
+#[derive(Debug, PartialEq, Eq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum ElementStatus {
+    Reported,
+    SensorOutage,
+    NotReported,
+}
+
+impl fmt::Display for SensorKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let code = match self {
+            SensorKind::Rvr => "RVRNO",
+            SensorKind::PresentWeather => "PWINO",
+            SensorKind::Precipitation => "PNO",
+            SensorKind::FreezingRain => "FZRANO",
+            SensorKind::Lightning => "TSNO",
+            SensorKind::Visibility => "VISNO",
+            SensorKind::Ceiling => "CHINO",
+        };
+        f.write_str(code)
+    }
+}
+
+impl fmt::Display for SensorOutage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.sensor)?;
+        if let Some(runway) = &self.runway {
+            write!(f, " RWY{}", runway)?;
+        }
+        Ok(())
+    }
+}
+
+/// Writes the outages followed by `$` when maintenance is required.
+impl fmt::Display for SensorStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.write_groups(&mut GroupWriter::new(f))
+    }
+}
+
+impl WriteGroups for SensorStatus {
+    fn write_groups(&self, w: &mut GroupWriter<'_, '_>) -> fmt::Result {
+        w.groups(&self.outages)?;
+        w.groups(self.maintenance_required.then_some("$"))
+    }
+}
+
+pub fn parse_sensor_outage(s: &str) -> IResult<&str, SensorOutage> {
+    map(
+        pair(
+            map_opt(
+                alt((
+                    tag("RVRNO"),
+                    tag("PWINO"),
+                    tag("PNO"),
+                    tag("FZRANO"),
+                    tag("TSNO"),
+                    tag("VISNO"),
+                    tag("CHINO"),
+                )),
+                code,
+            ),
+            opt(preceded(pair(multispace1, tag("RWY")), parse_runway)),
+        ),
+        |(sensor, runway)| SensorOutage { sensor, runway },
+    )(s)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_parse_sensor_outage() -> anyhow::Result<()> {
+        assert_eq!(
+            parse_sensor_outage("RVRNO")?.1,
+            SensorOutage {
+                sensor: SensorKind::Rvr,
+                runway: None
+            }
+        );
+        assert_eq!(
+            parse_sensor_outage("VISNO RWY06")?.1,
+            SensorOutage {
+                sensor: SensorKind::Visibility,
+                runway: Some(Runway {
+                    number: 6,
+                    position: None
+                })
+            }
+        );
+        assert!(parse_sensor_outage("RVR").is_err());
+        Ok(())
+    }
+}
@@ -0,0 +1,196 @@
+use std::fmt;
+
+use nom::{
+    branch::alt,
+    bytes::complete::tag,
+    combinator::{map, opt, verify},
+    sequence::{pair, preceded, terminated, tuple},
+    IResult,
+};
+
+use crate::rvr::{parse_runway, Runway};
+use crate::sea_state::{parse_sea_state, SeaState};
+use crate::weather::{
+    fmt_weather, parse_descriptor, parse_phenomenon, WeatherDescriptor, WeatherPhenomenon,
+};
+use crate::{end_of_group, lazy_many0, GroupWriter, WriteGroups};
+
+/// Significant weather observed since the previous report, e.g. `RESHRA`.
+#[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct RecentWeather {
+    pub descriptor: Option<WeatherDescriptor>,
+    pub phenomena: Vec<WeatherPhenomenon>,
+}
+
+#[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(
+    feature = "serde",
+    serde(tag = "type", content = "value", rename_all = "snake_case")
+)]
+pub enum WindShear {
+    Runway(Runway),
+    AllRunways,
+}
+
+#[derive(Debug, PartialEq, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Supplementary {
+    pub recent_weather: Vec<RecentWeather>,
+    pub wind_shear: Vec<WindShear>,
+    pub sea_state: Option<SeaState>,
+}
+
+impl fmt::Display for RecentWeather {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("RE")?;
+        fmt_weather(&self.descriptor, &self.phenomena, f)
+    }
+}
+
+impl fmt::Display for WindShear {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            WindShear::Runway(runway) => write!(f, "WS R{}", runway),
+            WindShear::AllRunways => f.write_str("WS ALL RWY"),
+        }
+    }
+}
+
+impl WriteGroups for Supplementary {
+    fn write_groups(&self, w: &mut GroupWriter<'_, '_>) -> fmt::Result {
+        w.groups(&self.recent_weather)?;
+        w.groups(&self.wind_shear)?;
+        w.groups(&self.sea_state)
+    }
+}
+
+impl fmt::Display for Supplementary {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.write_groups(&mut GroupWriter::new(f))
+    }
+}
+
+pub fn parse_recent_weather(s: &str) -> IResult<&str, RecentWeather> {
+    let s = s.trim_start();
+    let (rest, (descriptor, phenomena)) = terminated(
+        preceded(
+            tag("RE"),
+            verify(
+                pair(opt(parse_descriptor), lazy_many0(parse_phenomenon)),
+                |(descriptor, phenomena)| descriptor.is_some() || !phenomena.is_empty(),
+            ),
+        ),
+        end_of_group,
+    )(s)?;
+
+    Ok((
+        rest,
+        RecentWeather {
+            descriptor,
+            phenomena,
+        },
+    ))
+}
+
+pub fn parse_wind_shear(s: &str) -> IResult<&str, WindShear> {
+    let s = s.trim_start();
+    terminated(
+        preceded(
+            tag("WS "),
+            alt((
+                map(tag("ALL RWY"), |_| WindShear::AllRunways),
+                map(
+                    preceded(alt((tag("RWY"), tag("R"))), parse_runway),
+                    WindShear::Runway,
+                ),
+            )),
+        ),
+        end_of_group,
+    )(s)
+}
+
+pub fn parse_supplementary(s: &str) -> IResult<&str, Supplementary> {
+    let (rest, (recent_weather, wind_shear, sea_state)) = tuple((
+        lazy_many0(parse_recent_weather),
+        lazy_many0(parse_wind_shear),
+        opt(parse_sea_state),
+    ))(s)?;
+    Ok((
+        rest,
+        Supplementary {
+            recent_weather,
+            wind_shear,
+            sea_state,
+        },
+    ))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::rvr::RunwayPosition;
+
+    #[test]
+    fn test_parse_recent_weather() -> anyhow::Result<()> {
+        assert_eq!(
+            parse_recent_weather("RETS")?.1,
+            RecentWeather {
+                descriptor: Some(WeatherDescriptor::Thunderstorm),
+                phenomena: vec![]
+            }
+        );
+        assert_eq!(
+            parse_recent_weather("RESHRA")?.1,
+            RecentWeather {
+                descriptor: Some(WeatherDescriptor::Showers),
+                phenomena: vec![WeatherPhenomenon::Rain]
+            }
+        );
+        assert_eq!(
+            parse_recent_weather("REFZRA")?.1,
+            RecentWeather {
+                descriptor: Some(WeatherDescriptor::Freezing),
+                phenomena: vec![WeatherPhenomenon::Rain]
+            }
+        );
+        assert!(parse_recent_weather("RE").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_wind_shear() -> anyhow::Result<()> {
+        let runway_27l = WindShear::Runway(Runway {
+            number: 27,
+            position: Some(RunwayPosition::Left),
+        });
+        assert_eq!(parse_wind_shear("WS R27L")?.1, runway_27l);
+        assert_eq!(parse_wind_shear("WS RWY27L")?.1, runway_27l);
+        assert_eq!(parse_wind_shear("WS ALL RWY")?.1, WindShear::AllRunways);
+        assert!(parse_wind_shear("WS").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_supplementary() -> anyhow::Result<()> {
+        let (rest, supplementary) = parse_supplementary(" RETS WS R27L WS ALL RWY W15/S4 NOSIG")?;
+        assert_eq!(rest, " NOSIG");
+        assert_eq!(supplementary.recent_weather.len(), 1);
+        assert_eq!(supplementary.wind_shear.len(), 2);
+        assert_eq!(
+            supplementary.sea_state.map(|s| s.temperature),
+            Some(Some(15.into()))
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_display_supplementary() -> anyhow::Result<()> {
+        for sample in ["RESHSN", "RETS RERA WS R27L WS ALL RWY W15/S4", "WS R09"] {
+            assert_eq!(parse_supplementary(sample)?.1.to_string(), sample);
+        }
+        assert_eq!(Supplementary::default().to_string(), "");
+        Ok(())
+    }
+}
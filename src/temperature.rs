@@ -0,0 +1,211 @@
+use std::fmt;
+
+use nom::{
+    branch::alt,
+    bytes::complete::{tag, take_while_m_n},
+    combinator::{map, map_res, opt, value},
+    sequence::{pair, terminated, tuple},
+    IResult,
+};
+
+use crate::end_of_group;
+
+/// Whole degrees Celsius as a magnitude and the `M` prefix, so that `M00`
+/// (below zero, rounded to zero) stays distinct from `00`.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Degrees {
+    pub magnitude: u8,
+    pub negative: bool,
+}
+
+impl Degrees {
+    /// Degrees whose sign follows `value`.
+    pub fn new(value: i8) -> Self {
+        Degrees {
+            magnitude: value.unsigned_abs(),
+            negative: value < 0,
+        }
+    }
+
+    /// `M00`: below zero, rounded to zero.
+    pub fn minus_zero() -> Self {
+        Degrees {
+            magnitude: 0,
+            negative: true,
+        }
+    }
+
+    /// Signed value in degrees Celsius.
+    pub fn value(&self) -> i16 {
+        let magnitude = i16::from(self.magnitude);
+        if self.negative {
+            -magnitude
+        } else {
+            magnitude
+        }
+    }
+}
+
+impl From<i8> for Degrees {
+    fn from(value: i8) -> Self {
+        Degrees::new(value)
+    }
+}
+
+impl fmt::Display for Degrees {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.negative {
+            f.write_str("M")?;
+        }
+        write!(f, "{:02}", self.magnitude)
+    }
+}
+
+/// Air temperature and dew point in whole degrees Celsius. `None` stands
+/// for a value reported as missing (`//`) or left out of the group.
+#[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Temperature {
+    pub air: Option<Degrees>,
+    pub dew_point: Option<Degrees>,
+}
+
+impl Temperature {
+    /// Difference between air temperature and dew point, when both are known.
+    pub fn spread(&self) -> Option<i16> {
+        Some(self.air?.value() - self.dew_point?.value())
+    }
+}
+
+pub(crate) fn parse_degrees(s: &str) -> IResult<&str, Option<Degrees>> {
+    alt((
+        map(
+            map_res(
+                pair(
+                    opt(tag("M")),
+                    take_while_m_n(2, 2, |c: char| c.is_ascii_digit()),
+                ),
+                |(sign, digits): (Option<&str>, &str)| {
+                    digits.parse::<u8>().map(|magnitude| Degrees {
+                        magnitude,
+                        negative: sign.is_some(),
+                    })
+                },
+            ),
+            Some,
+        ),
+        value(None, tag("//")),
+    ))(s)
+}
+
+/// Writes whole degrees as parsed by [`parse_degrees`], e.g. `M04`, or `//`.
+pub(crate) fn fmt_degrees(degrees: Option<Degrees>, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    match degrees {
+        Some(d) => write!(f, "{}", d),
+        None => f.write_str("//"),
+    }
+}
+
+impl fmt::Display for Temperature {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt_degrees(self.air, f)?;
+        f.write_str("/")?;
+        fmt_degrees(self.dew_point, f)
+    }
+}
+
+pub fn parse_temperature(s: &str) -> IResult<&str, Temperature> {
+    let s = s.trim_start();
+    let (rest, (air, _, dew_point)) = terminated(
+        tuple((parse_degrees, tag("/"), opt(parse_degrees))),
+        end_of_group,
+    )(s)?;
+
+    Ok((
+        rest,
+        Temperature {
+            air,
+            dew_point: dew_point.flatten(),
+        },
+    ))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_parse_temperature() -> anyhow::Result<()> {
+        assert_eq!(
+            parse_temperature("M04/M07")?.1,
+            Temperature {
+                air: Some(Degrees::new(-4)),
+                dew_point: Some(Degrees::new(-7))
+            }
+        );
+        assert_eq!(
+            parse_temperature("22/15")?.1,
+            Temperature {
+                air: Some(Degrees::new(22)),
+                dew_point: Some(Degrees::new(15))
+            }
+        );
+        assert_eq!(
+            parse_temperature("01/M01")?.1,
+            Temperature {
+                air: Some(Degrees::new(1)),
+                dew_point: Some(Degrees::new(-1))
+            }
+        );
+        assert_eq!(parse_temperature("M04/M07")?.1.spread(), Some(3));
+        assert_eq!(
+            parse_temperature("M00/M02")?.1,
+            Temperature {
+                air: Some(Degrees::minus_zero()),
+                dew_point: Some(Degrees::new(-2))
+            }
+        );
+        assert_ne!(
+            parse_temperature("M00/M02")?.1,
+            parse_temperature("00/M02")?.1
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_spread_extremes() -> anyhow::Result<()> {
+        assert_eq!(parse_temperature("99/M99")?.1.spread(), Some(198));
+        assert_eq!(parse_temperature("M99/99")?.1.spread(), Some(-198));
+        assert_eq!(parse_temperature("M00/00")?.1.spread(), Some(0));
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_temperature_missing() -> anyhow::Result<()> {
+        let missing_dew_point = Temperature {
+            air: Some(Degrees::new(12)),
+            dew_point: None,
+        };
+        assert_eq!(parse_temperature("12/")?.1, missing_dew_point);
+        assert_eq!(parse_temperature("12///")?.1, missing_dew_point);
+        assert_eq!(
+            parse_temperature("/////")?.1,
+            Temperature {
+                air: None,
+                dew_point: None
+            }
+        );
+        assert!(parse_temperature("BKN022").is_err());
+        assert!(parse_temperature("1200").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_display_temperature() -> anyhow::Result<()> {
+        for sample in ["M04/M07", "22/15", "00/M01", "M00/M01", "12///", "/////"] {
+            assert_eq!(parse_temperature(sample)?.1.to_string(), sample);
+        }
+        Ok(())
+    }
+}
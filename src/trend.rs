@@ -0,0 +1,329 @@
+use std::fmt;
+use std::str::FromStr;
+
+use nom::{
+    branch::alt,
+    bytes::complete::{tag, take},
+    combinator::{map, map_opt, opt, value},
+    multi::many1,
+    sequence::{pair, preceded, terminated, tuple},
+    IResult,
+};
+
+use crate::clouds::{parse_clouds, CloudLayer};
+use crate::colour::{parse_colour_code, ColourCode};
+use crate::error::{MetarElement, MetarError};
+use crate::visibility::{parse_visibility, Visibility};
+use crate::weather::{parse_weather, PresentWeather};
+use crate::wind::{parse_wind, Wind};
+use crate::{code, decode, end_of_group, parse_with_bounds, Code, GroupWriter};
+
+#[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum ChangeIndicator {
+    Becoming,
+    Temporary,
+}
+
+impl Code for ChangeIndicator {
+    const ELEMENT: MetarElement = MetarElement::Trend;
+    const EXPECTED: &'static str = "BECMG or TEMPO";
+
+    fn from_code(s: &str) -> Option<Self> {
+        match s {
+            "BECMG" => Some(ChangeIndicator::Becoming),
+            "TEMPO" => Some(ChangeIndicator::Temporary),
+            _ => None,
+        }
+    }
+}
+
+impl FromStr for ChangeIndicator {
+    type Err = MetarError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        decode(s)
+    }
+}
+
+#[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TrendTime {
+    pub hour: u8,
+    pub minute: u8,
+}
+
+impl TrendTime {
+    /// Decodes the `HH` and `MM` digit pairs of an `FM`, `TL` or `AT` group.
+    fn from_digits(hour: &str, minute: &str) -> Option<TrendTime> {
+        Some(TrendTime {
+            hour: parse_with_bounds(0, 24, hour)?,
+            minute: parse_with_bounds(0, 59, minute)?,
+        })
+    }
+}
+
+/// A `BECMG` or `TEMPO` block. Elements that are not expected to change
+/// are left empty.
+#[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TrendChange {
+    pub indicator: ChangeIndicator,
+    pub from: Option<TrendTime>,
+    pub until: Option<TrendTime>,
+    pub at: Option<TrendTime>,
+    pub wind: Option<Wind>,
+    pub visibility: Option<Visibility>,
+    pub weather: Vec<PresentWeather>,
+    /// `NSW`: the significant weather is expected to end.
+    pub no_significant_weather: bool,
+    pub clouds: Vec<CloudLayer>,
+    pub colour: Option<ColourCode>,
+}
+
+#[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(
+    feature = "serde",
+    serde(tag = "type", content = "value", rename_all = "snake_case")
+)]
+pub enum Trend {
+    NoSignificantChange,
+    Changes(Vec<TrendChange>),
+}
+
+impl fmt::Display for ChangeIndicator {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ChangeIndicator::Becoming => f.write_str("BECMG"),
+            ChangeIndicator::Temporary => f.write_str("TEMPO"),
+        }
+    }
+}
+
+impl fmt::Display for TrendTime {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:02}{:02}", self.hour, self.minute)
+    }
+}
+
+impl fmt::Display for TrendChange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut w = GroupWriter::new(f);
+        w.group(&self.indicator)?;
+        for (prefix, time) in [("FM", &self.from), ("TL", &self.until), ("AT", &self.at)] {
+            if let Some(time) = time {
+                w.group(format_args!("{}{}", prefix, time))?;
+            }
+        }
+        w.groups(&self.wind)?;
+        w.groups(&self.visibility)?;
+        w.groups(&self.weather)?;
+        w.groups(self.no_significant_weather.then_some("NSW"))?;
+        w.groups(&self.clouds)?;
+        w.groups(&self.colour)
+    }
+}
+
+impl fmt::Display for Trend {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Trend::NoSignificantChange => f.write_str("NOSIG"),
+            Trend::Changes(changes) => GroupWriter::new(f).groups(changes),
+        }
+    }
+}
+
+fn trend_time<'a>(prefix: &'static str) -> impl FnMut(&'a str) -> IResult<&'a str, TrendTime> {
+    move |s: &'a str| {
+        let s = s.trim_start();
+        terminated(
+            preceded(
+                tag(prefix),
+                map_opt(pair(take(2usize), take(2usize)), |(hour, minute)| {
+                    TrendTime::from_digits(hour, minute)
+                }),
+            ),
+            end_of_group,
+        )(s)
+    }
+}
+
+fn parse_nsw(s: &str) -> IResult<&str, ()> {
+    let s = s.trim_start();
+    value((), terminated(tag("NSW"), end_of_group))(s)
+}
+
+fn parse_no_significant_weather(s: &str) -> IResult<&str, bool> {
+    map(opt(parse_nsw), |nsw| nsw.is_some())(s)
+}
+
+pub fn parse_trend_change(s: &str) -> IResult<&str, TrendChange> {
+    let s = s.trim_start();
+    let (
+        rest,
+        (
+            indicator,
+            from,
+            until,
+            at,
+            wind,
+            visibility,
+            weather,
+            no_significant_weather,
+            clouds,
+            colour,
+        ),
+    ) = tuple((
+        terminated(
+            map_opt(alt((tag("BECMG"), tag("TEMPO"))), code),
+            end_of_group,
+        ),
+        opt(trend_time("FM")),
+        opt(trend_time("TL")),
+        opt(trend_time("AT")),
+        opt(parse_wind),
+        opt(terminated(parse_visibility, end_of_group)),
+        parse_weather,
+        parse_no_significant_weather,
+        parse_clouds,
+        opt(parse_colour_code),
+    ))(s)?;
+
+    Ok((
+        rest,
+        TrendChange {
+            indicator,
+            from,
+            until,
+            at,
+            wind,
+            visibility,
+            weather,
+            no_significant_weather,
+            clouds,
+            colour,
+        },
+    ))
+}
+
+pub(crate) fn parse_nosig(s: &str) -> IResult<&str, Trend> {
+    let s = s.trim_start();
+    value(
+        Trend::NoSignificantChange,
+        terminated(tag("NOSIG"), end_of_group),
+    )(s)
+}
+
+pub fn parse_trend(s: &str) -> IResult<&str, Trend> {
+    alt((parse_nosig, map(many1(parse_trend_change), Trend::Changes)))(s)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::clouds::CloudCover;
+    use crate::visibility::VisibilityDistance;
+    use crate::weather::WeatherIntensity;
+
+    #[test]
+    fn test_parse_nosig() -> anyhow::Result<()> {
+        assert_eq!(parse_trend(" NOSIG")?.1, Trend::NoSignificantChange);
+        assert!(parse_trend("NOSIGN").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_trend_time_from_digits() {
+        assert_eq!(
+            TrendTime::from_digits("11", "00"),
+            Some(TrendTime {
+                hour: 11,
+                minute: 0
+            })
+        );
+        assert_eq!(TrendTime::from_digits("25", "00"), None);
+    }
+
+    #[test]
+    fn test_parse_becmg() -> anyhow::Result<()> {
+        let (rest, trend) = parse_trend("BECMG FM1100 TL1300 25035G50KT 3000 NSW BKN010")?;
+        assert_eq!(rest, "");
+        let Trend::Changes(changes) = trend else {
+            panic!("Expected trend changes")
+        };
+        assert_eq!(changes.len(), 1);
+        let change = &changes[0];
+        assert_eq!(change.indicator, ChangeIndicator::Becoming);
+        assert_eq!(
+            change.from,
+            Some(TrendTime {
+                hour: 11,
+                minute: 0
+            })
+        );
+        assert_eq!(
+            change.until,
+            Some(TrendTime {
+                hour: 13,
+                minute: 0
+            })
+        );
+        assert_eq!(change.at, None);
+        assert_eq!(change.wind.as_ref().and_then(|w| w.speed.value()), Some(35));
+        assert_eq!(
+            change.visibility,
+            Some(VisibilityDistance::Meters(3000).into())
+        );
+        assert!(change.no_significant_weather);
+        assert_eq!(
+            change.clouds,
+            vec![CloudLayer::Layer {
+                cover: Some(CloudCover::Broken),
+                height: Some(10),
+                cloud_type: None
+            }]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_multiple_changes() -> anyhow::Result<()> {
+        let (rest, trend) = parse_trend("TEMPO AT1530 -TSRA BECMG 9999 8849//91")?;
+        assert_eq!(rest, " 8849//91");
+        let Trend::Changes(changes) = trend else {
+            panic!("Expected trend changes")
+        };
+        assert_eq!(changes.len(), 2);
+        assert_eq!(changes[0].indicator, ChangeIndicator::Temporary);
+        assert_eq!(
+            changes[0].at,
+            Some(TrendTime {
+                hour: 15,
+                minute: 30
+            })
+        );
+        assert_eq!(changes[0].wind, None);
+        assert_eq!(changes[0].weather[0].intensity, WeatherIntensity::Light);
+        assert_eq!(
+            changes[1].visibility.as_ref().and_then(Visibility::meters),
+            Some(10_000.0)
+        );
+        assert_eq!(changes[1].colour, None);
+        Ok(())
+    }
+
+    #[test]
+    fn test_display_trend() -> anyhow::Result<()> {
+        for sample in [
+            "NOSIG",
+            "TEMPO 24025G35KT 4000 SHRA",
+            "BECMG FM1700 TL1800 9999 NSW BKN030 WHT",
+            "BECMG AT1800 CAVOK TEMPO FM1900 2000 +TSRA BKN010CB",
+        ] {
+            assert_eq!(parse_trend(sample)?.1.to_string(), sample);
+        }
+        Ok(())
+    }
+}
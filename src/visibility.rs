@@ -1,19 +1,27 @@
+use std::fmt;
 use std::str::FromStr;
 
 use nom::{
     branch::alt,
-    bytes::complete::{tag, take_while},
+    bytes::complete::{tag, take_while_m_n},
     character::{
         complete::{digit1, multispace1},
         is_digit,
     },
-    combinator::{map_res, opt},
-    error::{context, ErrorKind},
-    sequence::{pair, tuple},
+    combinator::{map, map_opt, map_res, not, opt, value},
+    error::context,
+    sequence::{pair, preceded, separated_pair, terminated, tuple},
     IResult,
 };
 
-#[derive(Debug, PartialEq)]
+use crate::error::{MetarElement, MetarError};
+use crate::{code, decode, end_of_group, lazy_many0, Code};
+
+const METERS_PER_STATUTE_MILE: f64 = 1609.344;
+
+#[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
 pub enum VisibilityDirection {
     North,
     NorthEast,
@@ -25,194 +33,545 @@ pub enum VisibilityDirection {
     NorthWest,
 }
 
+impl Code for VisibilityDirection {
+    const ELEMENT: MetarElement = MetarElement::Visibility;
+    const EXPECTED: &'static str = "N, NE, E, SE, S, SW, W or NW";
+
+    fn from_code(s: &str) -> Option<Self> {
+        match s {
+            "N" => Some(VisibilityDirection::North),
+            "NE" => Some(VisibilityDirection::NorthEast),
+            "E" => Some(VisibilityDirection::East),
+            "SE" => Some(VisibilityDirection::SouthEast),
+            "S" => Some(VisibilityDirection::South),
+            "SW" => Some(VisibilityDirection::SouthWest),
+            "W" => Some(VisibilityDirection::West),
+            "NW" => Some(VisibilityDirection::NorthWest),
+            _ => None,
+        }
+    }
+}
+
 impl FromStr for VisibilityDirection {
-    type Err = anyhow::Error;
+    type Err = MetarError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        match s {
-            "N" => Ok(VisibilityDirection::North),
-            "NE" => Ok(VisibilityDirection::NorthEast),
-            "E" => Ok(VisibilityDirection::East),
-            "SE" => Ok(VisibilityDirection::SouthEast),
-            "S" => Ok(VisibilityDirection::South),
-            "SW" => Ok(VisibilityDirection::SouthWest),
-            "W" => Ok(VisibilityDirection::West),
-            "NW" => Ok(VisibilityDirection::NorthWest),
-            _ => Err(anyhow::Error::msg("Erro while converting from str")),
+        decode(s)
+    }
+}
+
+/// Prevailing or directional distance, in the unit it was reported in.
+#[derive(Debug, PartialEq, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(
+    feature = "serde",
+    serde(tag = "type", content = "value", rename_all = "snake_case")
+)]
+pub enum VisibilityDistance {
+    Meters(u16),
+    StatuteMiles(f64),
+}
+
+impl VisibilityDistance {
+    pub fn meters(&self) -> f64 {
+        match self {
+            VisibilityDistance::Meters(m) => *m as f64,
+            VisibilityDistance::StatuteMiles(sm) => sm * METERS_PER_STATUTE_MILE,
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum VisibilityQualifier {
+    /// `M`: less than the reported value, e.g. `M1/4SM`.
+    LessThan,
+    /// `P`: more than the reported value, e.g. `P6SM`. `9999` is decoded as
+    /// 10 km with this qualifier.
+    MoreThan,
+}
+
+#[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct VisibilityValue {
+    pub distance: VisibilityDistance,
+    pub qualifier: Option<VisibilityQualifier>,
+}
+
+impl VisibilityValue {
+    pub fn meters(&self) -> f64 {
+        self.distance.meters()
+    }
+}
+
+impl From<VisibilityDistance> for VisibilityValue {
+    fn from(distance: VisibilityDistance) -> Self {
+        VisibilityValue {
+            distance,
+            qualifier: None,
         }
     }
 }
 
-#[derive(Debug, PartialEq)]
+/// Minimum visibility towards one direction, e.g. `1200NW`.
+#[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DirectionalVisibility {
+    pub value: VisibilityValue,
+    pub direction: VisibilityDirection,
+}
+
+#[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(
+    feature = "serde",
+    serde(tag = "type", content = "value", rename_all = "snake_case")
+)]
 pub enum Visibility {
-    Meters(u16),
-    StatuateMiles(f64),
+    Prevailing {
+        value: VisibilityValue,
+        /// `NDV`: the station cannot report directional variations.
+        no_directional_variation: bool,
+        directional: Vec<DirectionalVisibility>,
+    },
     Cavok,
-    Nsc,
-    Skc,
-    CustomDirection(Box<Visibility>, Box<Visibility>, VisibilityDirection),
+    /// `////`: visibility not available.
+    Missing,
 }
+
 impl Visibility {
-    fn from_tuple(
-        t: (Visibility, Option<(Visibility, &str)>),
-    ) -> Result<Visibility, anyhow::Error> {
-        match t.1 {
-            Some(v) => Ok(Visibility::CustomDirection(
-                Box::new(t.0),
-                Box::new(v.0),
-                VisibilityDirection::from_str(v.1)?,
-            )),
-            None => Ok(t.0),
+    /// Prevailing visibility in meters. `CAVOK` counts as 10 km.
+    pub fn meters(&self) -> Option<f64> {
+        match self {
+            Visibility::Prevailing { value, .. } => Some(value.meters()),
+            Visibility::Cavok => Some(10_000.0),
+            Visibility::Missing => None,
+        }
+    }
+}
+
+impl From<VisibilityDistance> for Visibility {
+    fn from(distance: VisibilityDistance) -> Self {
+        Visibility::Prevailing {
+            value: distance.into(),
+            no_directional_variation: false,
+            directional: vec![],
         }
     }
 }
 
 impl FromStr for Visibility {
-    type Err = anyhow::Error;
+    type Err = MetarError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         match s {
-            "CAVOK" => return Ok(Visibility::Cavok),
-            "NSC" => return Ok(Visibility::Nsc),
-            "SKC" => return Ok(Visibility::Skc),
-            _ => (),
+            "CAVOK" => Ok(Visibility::Cavok),
+            "////" => Ok(Visibility::Missing),
+            _ => parse_visibility_value(s)
+                .ok()
+                .filter(|(rest, _)| rest.is_empty())
+                .map(|(_, value)| Visibility::Prevailing {
+                    value,
+                    no_directional_variation: false,
+                    directional: vec![],
+                })
+                .ok_or_else(|| {
+                    MetarError::invalid(
+                        MetarElement::Visibility,
+                        s,
+                        "visibility such as 9999, 1/2SM or CAVOK",
+                    )
+                }),
         }
+    }
+}
+
+/// Writes statute miles the way [`parse_statute_miles`] reads them back,
+/// e.g. `1 1/2` for 1.5. Values that no fraction up to sixteenths
+/// represents exactly are rounded to the nearest sixteenth.
+pub(crate) fn fmt_statute_miles(miles: f64, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    let split = |denominator: u32| {
+        let parts = (miles * denominator as f64).round() as u32;
+        (parts / denominator, parts % denominator, denominator)
+    };
+    let (whole, numerator, denominator) = (1..=16)
+        .map(split)
+        .find(|&(whole, numerator, denominator)| {
+            let fraction = numerator as f64 / denominator as f64;
+            let value = match (whole, numerator) {
+                (_, 0) => whole as f64,
+                (0, _) => fraction,
+                _ => whole as f64 + fraction,
+            };
+            value == miles
+        })
+        .unwrap_or_else(|| split(16));
 
-        s.parse::<u16>()
-            .map(Visibility::Meters)
-            .map_err(|_| anyhow::anyhow!("Cannot parse into Visibility"))
+    match (whole, numerator) {
+        (_, 0) => write!(f, "{}", whole),
+        (0, _) => write!(f, "{}/{}", numerator, denominator),
+        _ => write!(f, "{} {}/{}", whole, numerator, denominator),
     }
 }
 
-fn parse_partial(
-    s: &str,
-) -> IResult<&str, (Option<(&str, &str)>, &str, Option<&str>, Option<&str>, &str)> {
-    tuple((
-        opt(pair(digit1, multispace1)),
-        take_while(|c: char| is_digit(c as u8)),
-        opt(tag("/")),
-        opt(digit1),
-        tag("SM"),
-    ))(s)
+impl fmt::Display for VisibilityDirection {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let direction = match self {
+            VisibilityDirection::North => "N",
+            VisibilityDirection::NorthEast => "NE",
+            VisibilityDirection::East => "E",
+            VisibilityDirection::SouthEast => "SE",
+            VisibilityDirection::South => "S",
+            VisibilityDirection::SouthWest => "SW",
+            VisibilityDirection::West => "W",
+            VisibilityDirection::NorthWest => "NW",
+        };
+        f.write_str(direction)
+    }
 }
 
-fn partial_statuate_miles_parser(s: &str) -> IResult<&str, Visibility> {
-    let (remainer, (d0, d1, t, d2, _)) = parse_partial(s)?;
+/// Meters are written on four digits, statute miles as a mixed number
+/// followed by `SM`, e.g. `1 1/2SM`.
+impl fmt::Display for VisibilityDistance {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            VisibilityDistance::Meters(m) => write!(f, "{:04}", (*m).min(9999)),
+            VisibilityDistance::StatuteMiles(sm) => {
+                fmt_statute_miles(*sm, f)?;
+                f.write_str("SM")
+            }
+        }
+    }
+}
+
+impl fmt::Display for VisibilityQualifier {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            VisibilityQualifier::LessThan => f.write_str("M"),
+            VisibilityQualifier::MoreThan => f.write_str("P"),
+        }
+    }
+}
 
-    if let Some(sep) = t {
-        if sep == "/" {
-            if let Some(denominator) = d2 {
-                let numerator: f64 = d1.parse().unwrap_or_default();
-                let denominator: f64 = denominator.parse().unwrap_or_default();
-                let mut partial_res = numerator / denominator;
-                if let Some((whole, _)) = d0 {
-                    partial_res += whole.parse::<f64>().unwrap_or_default()
+/// Meters have no qualifier in the report: 10 km or more is written `9999`.
+impl fmt::Display for VisibilityValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match (&self.distance, &self.qualifier) {
+            (VisibilityDistance::Meters(_), _) => write!(f, "{}", self.distance),
+            (_, Some(qualifier)) => write!(f, "{}{}", qualifier, self.distance),
+            (_, None) => write!(f, "{}", self.distance),
+        }
+    }
+}
+
+impl fmt::Display for DirectionalVisibility {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}{}", self.value, self.direction)
+    }
+}
+
+impl fmt::Display for Visibility {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Visibility::Prevailing {
+                value,
+                no_directional_variation,
+                directional,
+            } => {
+                write!(f, "{}", value)?;
+                if *no_directional_variation {
+                    f.write_str("NDV")?;
                 }
-                return Ok((remainer, Visibility::StatuateMiles(partial_res)));
+                for directional in directional {
+                    write!(f, " {}", directional)?;
+                }
+                Ok(())
             }
-        };
-    } else {
-        return Ok((
-            remainer,
-            Visibility::StatuateMiles(d1.parse().unwrap_or_default()),
-        ));
+            Visibility::Cavok => f.write_str("CAVOK"),
+            Visibility::Missing => f.write_str("////"),
+        }
     }
-    panic!("Not a valid partial")
 }
 
-pub fn parse_visibility(s: &str) -> IResult<&str, Visibility> {
-    let s = s.trim_start();
+/// A fraction such as `1/2` or `3/16`. The denominator must end the number,
+/// so that a following group like the `4/021` snow depth is never taken for
+/// one.
+fn parse_fraction(s: &str) -> IResult<&str, f64> {
+    map_opt(
+        terminated(
+            separated_pair(
+                take_while_m_n(1, 1, |c: char| c.is_ascii_digit()),
+                tag("/"),
+                take_while_m_n(1, 2, |c: char| c.is_ascii_digit()),
+            ),
+            not(digit1),
+        ),
+        |(numerator, denominator): (&str, &str)| {
+            let numerator: f64 = numerator.parse().ok()?;
+            let denominator: f64 = denominator.parse().ok()?;
+            (denominator != 0.0).then(|| numerator / denominator)
+        },
+    )(s)
+}
+
+/// Statute miles as a whole number, a fraction or a mixed number such as
+/// `1 1/2`.
+pub(crate) fn parse_statute_miles(s: &str) -> IResult<&str, f64> {
     alt((
-        map_res(tag("CAVOK"), |_| Visibility::from_str("CAVOK")),
-        map_res(tag("NSC"), |_| Visibility::from_str("NSC")),
-        map_res(tag("SKC"), |_| Visibility::from_str("SKC")),
-        partial_statuate_miles_parser,
-        context(
-            "Visibility Meters",
-            map_res(take_while(|x: char| is_digit(x as u8)), |s: &str| {
-                s.parse()
-                    .map_err(|_| nom::Err::Error(nom::error::Error::new(s, ErrorKind::Digit)))
-            }),
+        map(
+            separated_pair(digit1, multispace1, parse_fraction),
+            |(whole, fraction): (&str, f64)| whole.parse::<f64>().unwrap_or_default() + fraction,
         ),
+        parse_fraction,
+        map_res(digit1, str::parse),
     ))(s)
 }
 
-pub fn parse_visibility_full(s: &str) -> IResult<&str, Visibility> {
-    let carinal_tags = alt((
-        tag("NW"),
-        tag("NE"),
-        tag("SE"),
-        tag("SW"),
-        tag("N"),
-        tag("E"),
-        tag("S"),
-        tag("W"),
-    ));
-    let s = s.trim();
-    map_res(
-        tuple((
-            parse_visibility,
-            opt(tuple((parse_visibility, carinal_tags))),
-        )),
-        |res| {
-            Visibility::from_tuple(res).map_err(|_| {
-                nom::Err::Error(nom::error::Error::new(
-                    "Error while parsing Custom Direction",
-                    ErrorKind::Digit,
-                ))
-            })
+fn parse_qualifier(s: &str) -> IResult<&str, VisibilityQualifier> {
+    alt((
+        value(VisibilityQualifier::LessThan, tag("M")),
+        value(VisibilityQualifier::MoreThan, tag("P")),
+    ))(s)
+}
+
+fn partial_statuate_miles_parser(s: &str) -> IResult<&str, VisibilityValue> {
+    map(
+        pair(
+            opt(parse_qualifier),
+            terminated(parse_statute_miles, tag("SM")),
+        ),
+        |(qualifier, miles)| VisibilityValue {
+            distance: VisibilityDistance::StatuteMiles(miles),
+            qualifier,
         },
     )(s)
 }
+
+fn parse_meters(s: &str) -> IResult<&str, VisibilityValue> {
+    context(
+        "Visibility Meters",
+        map_res(
+            take_while_m_n(4, 4, |x: char| is_digit(x as u8)),
+            |m: &str| -> Result<VisibilityValue, std::num::ParseIntError> {
+                Ok(match m.parse()? {
+                    9999 => VisibilityValue {
+                        distance: VisibilityDistance::Meters(10_000),
+                        qualifier: Some(VisibilityQualifier::MoreThan),
+                    },
+                    m => VisibilityDistance::Meters(m).into(),
+                })
+            },
+        ),
+    )(s)
+}
+
+fn parse_visibility_value(s: &str) -> IResult<&str, VisibilityValue> {
+    alt((partial_statuate_miles_parser, parse_meters))(s)
+}
+
+fn parse_directional_visibility(s: &str) -> IResult<&str, DirectionalVisibility> {
+    map(
+        preceded(
+            multispace1,
+            terminated(pair(parse_visibility_value, parse_direction), end_of_group),
+        ),
+        |(value, direction)| DirectionalVisibility { value, direction },
+    )(s)
+}
+
+/// Compass direction of a directional visibility, e.g. `NE`.
+pub(crate) fn parse_direction(s: &str) -> IResult<&str, VisibilityDirection> {
+    map_opt(
+        alt((
+            tag("NW"),
+            tag("NE"),
+            tag("SE"),
+            tag("SW"),
+            tag("N"),
+            tag("E"),
+            tag("S"),
+            tag("W"),
+        )),
+        code,
+    )(s)
+}
+
+pub fn parse_visibility(s: &str) -> IResult<&str, Visibility> {
+    let s = s.trim_start();
+    alt((
+        value(Visibility::Cavok, terminated(tag("CAVOK"), end_of_group)),
+        value(Visibility::Missing, terminated(tag("////"), end_of_group)),
+        map(
+            tuple((
+                parse_visibility_value,
+                terminated(opt(tag("NDV")), end_of_group),
+                lazy_many0(parse_directional_visibility),
+            )),
+            |(value, ndv, directional)| Visibility::Prevailing {
+                value,
+                no_directional_variation: ndv.is_some(),
+                directional,
+            },
+        ),
+    ))(s)
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
+
+    fn meters(m: u16) -> Visibility {
+        VisibilityDistance::Meters(m).into()
+    }
+
+    fn miles(sm: f64) -> Visibility {
+        VisibilityDistance::StatuteMiles(sm).into()
+    }
+
     #[test]
     fn test_parse_visibility() -> anyhow::Result<()> {
         assert_eq!(parse_visibility("CAVOK")?.1, Visibility::Cavok);
-        assert_eq!(parse_visibility("NSC")?.1, Visibility::Nsc);
-        assert_eq!(parse_visibility("SKC")?.1, Visibility::Skc);
-        assert_eq!(parse_visibility("9999")?.1, Visibility::Meters(9999));
-        assert_eq!(parse_visibility("5000")?.1, Visibility::Meters(5000));
-        assert_eq!(parse_visibility(" 1000")?.1, Visibility::Meters(1000));
+        assert!(parse_visibility("NSC").is_err());
+        assert!(parse_visibility("SKC").is_err());
+        assert_eq!(parse_visibility("5000")?.1, meters(5000));
+        assert_eq!(parse_visibility(" 1000")?.1, meters(1000));
+        assert_eq!(parse_visibility("0000")?.1, meters(0));
+        assert_eq!(parse_visibility("1/4SM")?.1, miles(0.25));
+        assert_eq!(parse_visibility("10SM")?.1, miles(10.0));
+        assert_eq!(parse_visibility("1 1/2SM")?.1, miles(1.5));
+        assert!(parse_visibility("50000").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_visibility_qualifiers() -> anyhow::Result<()> {
+        let prevailing = |distance, qualifier| Visibility::Prevailing {
+            value: VisibilityValue {
+                distance,
+                qualifier: Some(qualifier),
+            },
+            no_directional_variation: false,
+            directional: vec![],
+        };
         assert_eq!(
-            parse_visibility("1/4SM")?.1,
-            Visibility::StatuateMiles(0.25)
+            parse_visibility("9999")?.1,
+            prevailing(
+                VisibilityDistance::Meters(10_000),
+                VisibilityQualifier::MoreThan
+            )
         );
-        assert_eq!(parse_visibility("10SM")?.1, Visibility::StatuateMiles(10.0));
         assert_eq!(
-            parse_visibility("1 1/2SM")?.1,
-            Visibility::StatuateMiles(1.5)
+            parse_visibility("M1/4SM")?.1,
+            prevailing(
+                VisibilityDistance::StatuteMiles(0.25),
+                VisibilityQualifier::LessThan
+            )
         );
+        assert_eq!(
+            parse_visibility("P6SM")?.1,
+            prevailing(
+                VisibilityDistance::StatuteMiles(6.0),
+                VisibilityQualifier::MoreThan
+            )
+        );
+        assert_eq!(parse_visibility("////")?.1, Visibility::Missing);
+        assert_eq!(Visibility::Missing.meters(), None);
+        assert_eq!(parse_visibility("9999")?.1.meters(), Some(10_000.0));
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_visibility_ndv() -> anyhow::Result<()> {
+        let (rest, visibility) = parse_visibility("9999NDV FEW020")?;
+        assert_eq!(rest, " FEW020");
+        assert!(matches!(
+            visibility,
+            Visibility::Prevailing {
+                no_directional_variation: true,
+                ..
+            }
+        ));
         Ok(())
     }
 
     #[test]
     fn test_parse_visibility_custom_direction() -> anyhow::Result<()> {
+        let directional = |distance, direction| DirectionalVisibility {
+            value: VisibilityValue::from(distance),
+            direction,
+        };
         assert_eq!(
-            parse_visibility_full("2000 1200NW")?.1,
-            Visibility::CustomDirection(
-                Box::new(Visibility::Meters(2000)),
-                Box::new(Visibility::Meters(1200)),
-                VisibilityDirection::NorthWest
-            )
+            parse_visibility("2000 1200NW")?.1,
+            Visibility::Prevailing {
+                value: VisibilityDistance::Meters(2000).into(),
+                no_directional_variation: false,
+                directional: vec![directional(
+                    VisibilityDistance::Meters(1200),
+                    VisibilityDirection::NorthWest
+                )],
+            }
         );
         assert_eq!(
-            parse_visibility_full("3000 2000S")?.1,
-            Visibility::CustomDirection(
-                Box::new(Visibility::Meters(3000)),
-                Box::new(Visibility::Meters(2000)),
-                VisibilityDirection::South
+            parse_visibility("3000 2000S 1500NE BR")?,
+            (
+                " BR",
+                Visibility::Prevailing {
+                    value: VisibilityDistance::Meters(3000).into(),
+                    no_directional_variation: false,
+                    directional: vec![
+                        directional(VisibilityDistance::Meters(2000), VisibilityDirection::South),
+                        directional(
+                            VisibilityDistance::Meters(1500),
+                            VisibilityDirection::NorthEast
+                        ),
+                    ],
+                }
             )
         );
         assert_eq!(
-            parse_visibility_full("1 1/2SM 10SMS")?.1,
-            Visibility::CustomDirection(
-                Box::new(Visibility::StatuateMiles(1.5)),
-                Box::new(Visibility::StatuateMiles(10.0)),
-                VisibilityDirection::South
-            )
+            parse_visibility("1 1/2SM 10SMS")?.1,
+            Visibility::Prevailing {
+                value: VisibilityDistance::StatuteMiles(1.5).into(),
+                no_directional_variation: false,
+                directional: vec![directional(
+                    VisibilityDistance::StatuteMiles(10.0),
+                    VisibilityDirection::South
+                )],
+            }
         );
         Ok(())
     }
+
+    #[test]
+    fn test_visibility_from_str() -> anyhow::Result<()> {
+        assert_eq!("CAVOK".parse::<Visibility>()?, Visibility::Cavok);
+        assert_eq!("////".parse::<Visibility>()?, Visibility::Missing);
+        assert_eq!("0800".parse::<Visibility>()?, meters(800));
+        assert!("0800X".parse::<Visibility>().is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_display_visibility() -> anyhow::Result<()> {
+        assert_eq!(miles(1.5).to_string(), "1 1/2SM");
+        assert_eq!(miles(0.3).to_string(), "3/10SM");
+        assert_eq!(miles(1.3).to_string(), "1 3/10SM");
+        assert_eq!(meters(10_000).to_string(), "9999");
+        for sample in [
+            "9999",
+            "0800",
+            "CAVOK",
+            "////",
+            "1/16SM",
+            "2 3/4SM",
+            "M1/4SM",
+            "P6SM",
+            "9999NDV",
+            "4000 1500NE 2000S",
+        ] {
+            assert_eq!(parse_visibility(sample)?.1.to_string(), sample);
+        }
+        Ok(())
+    }
 }
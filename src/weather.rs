@@ -0,0 +1,510 @@
+use std::fmt;
+use std::str::FromStr;
+
+use nom::{
+    branch::alt,
+    bytes::complete::{tag, take},
+    combinator::{map_opt, opt, value, verify},
+    multi::{fold_many0, many0},
+    sequence::{terminated, tuple},
+    IResult,
+};
+
+use crate::error::{MetarElement, MetarError};
+use crate::{code, decode, end_of_group, lazy_many0, Code};
+
+#[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum WeatherIntensity {
+    Light,
+    Moderate,
+    Heavy,
+    InVicinity,
+}
+
+impl Code for WeatherIntensity {
+    const ELEMENT: MetarElement = MetarElement::Weather;
+    const EXPECTED: &'static str = "-, + or VC";
+
+    fn from_code(s: &str) -> Option<Self> {
+        match s {
+            "-" => Some(WeatherIntensity::Light),
+            "+" => Some(WeatherIntensity::Heavy),
+            "VC" => Some(WeatherIntensity::InVicinity),
+            "" => Some(WeatherIntensity::Moderate),
+            _ => None,
+        }
+    }
+}
+
+impl FromStr for WeatherIntensity {
+    type Err = MetarError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        decode(s)
+    }
+}
+
+#[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum WeatherDescriptor {
+    Shallow,
+    Patches,
+    Partial,
+    LowDrifting,
+    Blowing,
+    Showers,
+    Thunderstorm,
+    Freezing,
+}
+
+impl Code for WeatherDescriptor {
+    const ELEMENT: MetarElement = MetarElement::Weather;
+    const EXPECTED: &'static str = "weather descriptor such as SH or TS";
+
+    fn from_code(s: &str) -> Option<Self> {
+        match s {
+            "MI" => Some(WeatherDescriptor::Shallow),
+            "BC" => Some(WeatherDescriptor::Patches),
+            "PR" => Some(WeatherDescriptor::Partial),
+            "DR" => Some(WeatherDescriptor::LowDrifting),
+            "BL" => Some(WeatherDescriptor::Blowing),
+            "SH" => Some(WeatherDescriptor::Showers),
+            "TS" => Some(WeatherDescriptor::Thunderstorm),
+            "FZ" => Some(WeatherDescriptor::Freezing),
+            _ => None,
+        }
+    }
+}
+
+impl FromStr for WeatherDescriptor {
+    type Err = MetarError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        decode(s)
+    }
+}
+
+#[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum PhenomenonCategory {
+    Precipitation,
+    Obscuration,
+    Other,
+}
+
+#[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum WeatherPhenomenon {
+    // Precipitation
+    Drizzle,
+    Rain,
+    Snow,
+    SnowGrains,
+    IceCrystals,
+    IcePellets,
+    Hail,
+    SmallHail,
+    UnknownPrecipitation,
+    // Obscuration
+    Mist,
+    Fog,
+    Smoke,
+    VolcanicAsh,
+    Dust,
+    Sand,
+    Haze,
+    Spray,
+    // Other
+    DustWhirls,
+    Squalls,
+    FunnelCloud,
+    Sandstorm,
+    Duststorm,
+    /// `//`: present weather not observed, usually a missing sensor on an
+    /// automated station.
+    NotObserved,
+}
+
+impl WeatherPhenomenon {
+    pub fn category(&self) -> PhenomenonCategory {
+        use WeatherPhenomenon::*;
+        match self {
+            Drizzle | Rain | Snow | SnowGrains | IceCrystals | IcePellets | Hail | SmallHail
+            | UnknownPrecipitation => PhenomenonCategory::Precipitation,
+            Mist | Fog | Smoke | VolcanicAsh | Dust | Sand | Haze | Spray => {
+                PhenomenonCategory::Obscuration
+            }
+            DustWhirls | Squalls | FunnelCloud | Sandstorm | Duststorm | NotObserved => {
+                PhenomenonCategory::Other
+            }
+        }
+    }
+}
+
+impl Code for WeatherPhenomenon {
+    const ELEMENT: MetarElement = MetarElement::Weather;
+    const EXPECTED: &'static str = "weather phenomenon such as RA or BR";
+
+    fn from_code(s: &str) -> Option<Self> {
+        match s {
+            "DZ" => Some(WeatherPhenomenon::Drizzle),
+            "RA" => Some(WeatherPhenomenon::Rain),
+            "SN" => Some(WeatherPhenomenon::Snow),
+            "SG" => Some(WeatherPhenomenon::SnowGrains),
+            "IC" => Some(WeatherPhenomenon::IceCrystals),
+            "PL" => Some(WeatherPhenomenon::IcePellets),
+            "GR" => Some(WeatherPhenomenon::Hail),
+            "GS" => Some(WeatherPhenomenon::SmallHail),
+            "UP" => Some(WeatherPhenomenon::UnknownPrecipitation),
+            "BR" => Some(WeatherPhenomenon::Mist),
+            "FG" => Some(WeatherPhenomenon::Fog),
+            "FU" => Some(WeatherPhenomenon::Smoke),
+            "VA" => Some(WeatherPhenomenon::VolcanicAsh),
+            "DU" => Some(WeatherPhenomenon::Dust),
+            "SA" => Some(WeatherPhenomenon::Sand),
+            "HZ" => Some(WeatherPhenomenon::Haze),
+            "PY" => Some(WeatherPhenomenon::Spray),
+            "PO" => Some(WeatherPhenomenon::DustWhirls),
+            "SQ" => Some(WeatherPhenomenon::Squalls),
+            "FC" => Some(WeatherPhenomenon::FunnelCloud),
+            "SS" => Some(WeatherPhenomenon::Sandstorm),
+            "DS" => Some(WeatherPhenomenon::Duststorm),
+            "//" => Some(WeatherPhenomenon::NotObserved),
+            _ => None,
+        }
+    }
+}
+
+impl FromStr for WeatherPhenomenon {
+    type Err = MetarError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        decode(s)
+    }
+}
+
+#[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PresentWeather {
+    pub intensity: WeatherIntensity,
+    pub descriptor: Option<WeatherDescriptor>,
+    pub phenomena: Vec<WeatherPhenomenon>,
+}
+
+impl PresentWeather {
+    /// Whether the group is the `//` placeholder rather than observed weather.
+    pub fn is_not_observed(&self) -> bool {
+        self.phenomena == [WeatherPhenomenon::NotObserved]
+    }
+}
+
+impl fmt::Display for WeatherIntensity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let intensity = match self {
+            WeatherIntensity::Light => "-",
+            WeatherIntensity::Moderate => "",
+            WeatherIntensity::Heavy => "+",
+            WeatherIntensity::InVicinity => "VC",
+        };
+        f.write_str(intensity)
+    }
+}
+
+impl fmt::Display for WeatherDescriptor {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let descriptor = match self {
+            WeatherDescriptor::Shallow => "MI",
+            WeatherDescriptor::Patches => "BC",
+            WeatherDescriptor::Partial => "PR",
+            WeatherDescriptor::LowDrifting => "DR",
+            WeatherDescriptor::Blowing => "BL",
+            WeatherDescriptor::Showers => "SH",
+            WeatherDescriptor::Thunderstorm => "TS",
+            WeatherDescriptor::Freezing => "FZ",
+        };
+        f.write_str(descriptor)
+    }
+}
+
+impl fmt::Display for WeatherPhenomenon {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let phenomenon = match self {
+            WeatherPhenomenon::Drizzle => "DZ",
+            WeatherPhenomenon::Rain => "RA",
+            WeatherPhenomenon::Snow => "SN",
+            WeatherPhenomenon::SnowGrains => "SG",
+            WeatherPhenomenon::IceCrystals => "IC",
+            WeatherPhenomenon::IcePellets => "PL",
+            WeatherPhenomenon::Hail => "GR",
+            WeatherPhenomenon::SmallHail => "GS",
+            WeatherPhenomenon::UnknownPrecipitation => "UP",
+            WeatherPhenomenon::Mist => "BR",
+            WeatherPhenomenon::Fog => "FG",
+            WeatherPhenomenon::Smoke => "FU",
+            WeatherPhenomenon::VolcanicAsh => "VA",
+            WeatherPhenomenon::Dust => "DU",
+            WeatherPhenomenon::Sand => "SA",
+            WeatherPhenomenon::Haze => "HZ",
+            WeatherPhenomenon::Spray => "PY",
+            WeatherPhenomenon::DustWhirls => "PO",
+            WeatherPhenomenon::Squalls => "SQ",
+            WeatherPhenomenon::FunnelCloud => "FC",
+            WeatherPhenomenon::Sandstorm => "SS",
+            WeatherPhenomenon::Duststorm => "DS",
+            WeatherPhenomenon::NotObserved => "//",
+        };
+        f.write_str(phenomenon)
+    }
+}
+
+/// Writes an optional descriptor followed by phenomena, e.g. `SHRASN`.
+pub(crate) fn fmt_weather(
+    descriptor: &Option<WeatherDescriptor>,
+    phenomena: &[WeatherPhenomenon],
+    f: &mut fmt::Formatter<'_>,
+) -> fmt::Result {
+    if let Some(descriptor) = descriptor {
+        write!(f, "{}", descriptor)?;
+    }
+    phenomena
+        .iter()
+        .try_for_each(|phenomenon| write!(f, "{}", phenomenon))
+}
+
+impl fmt::Display for PresentWeather {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.intensity)?;
+        fmt_weather(&self.descriptor, &self.phenomena, f)
+    }
+}
+
+fn parse_intensity(s: &str) -> IResult<&str, WeatherIntensity> {
+    let parser = opt(alt((tag("-"), tag("+"), tag("VC"))));
+    map_opt(parser, |x: Option<&str>| code(x.unwrap_or("")))(s)
+}
+
+pub(crate) fn parse_descriptor(s: &str) -> IResult<&str, WeatherDescriptor> {
+    map_opt(take(2usize), code)(s)
+}
+
+pub(crate) fn parse_phenomenon(s: &str) -> IResult<&str, WeatherPhenomenon> {
+    map_opt(take(2usize), code)(s)
+}
+
+/// Whether the parts read from a group make up present weather, given the
+/// number of phenomena and whether `//` is one of them.
+fn is_weather_group(
+    intensity: &WeatherIntensity,
+    descriptor: &Option<WeatherDescriptor>,
+    phenomena: usize,
+    not_observed: bool,
+) -> bool {
+    if not_observed {
+        // `//` only stands on its own; `//////` is a cloud group.
+        *intensity == WeatherIntensity::Moderate && descriptor.is_none() && phenomena == 1
+    } else {
+        descriptor.is_some() || phenomena > 0
+    }
+}
+
+/// Accepts the same groups as [`parse_present_weather`], but counts the
+/// phenomena instead of collecting them, so that it never allocates.
+pub(crate) fn check_present_weather(s: &str) -> IResult<&str, ()> {
+    let s = s.trim_start();
+    value(
+        (),
+        terminated(
+            verify(
+                tuple((
+                    parse_intensity,
+                    opt(parse_descriptor),
+                    fold_many0(
+                        parse_phenomenon,
+                        || (0, false),
+                        |(count, not_observed), phenomenon| {
+                            (
+                                count + 1,
+                                not_observed || phenomenon == WeatherPhenomenon::NotObserved,
+                            )
+                        },
+                    ),
+                )),
+                |(intensity, descriptor, (count, not_observed))| {
+                    is_weather_group(intensity, descriptor, *count, *not_observed)
+                },
+            ),
+            end_of_group,
+        ),
+    )(s)
+}
+
+pub fn parse_present_weather(s: &str) -> IResult<&str, PresentWeather> {
+    let s = s.trim_start();
+    let (rest, (intensity, descriptor, phenomena)) = terminated(
+        verify(
+            tuple((
+                parse_intensity,
+                opt(parse_descriptor),
+                lazy_many0(parse_phenomenon),
+            )),
+            |(intensity, descriptor, phenomena)| {
+                is_weather_group(
+                    intensity,
+                    descriptor,
+                    phenomena.len(),
+                    phenomena.contains(&WeatherPhenomenon::NotObserved),
+                )
+            },
+        ),
+        end_of_group,
+    )(s)?;
+
+    Ok((
+        rest,
+        PresentWeather {
+            intensity,
+            descriptor,
+            phenomena,
+        },
+    ))
+}
+
+pub fn parse_weather(s: &str) -> IResult<&str, Vec<PresentWeather>> {
+    many0(parse_present_weather)(s)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_parse_present_weather() -> anyhow::Result<()> {
+        assert_eq!(
+            parse_present_weather("+SN")?.1,
+            PresentWeather {
+                intensity: WeatherIntensity::Heavy,
+                descriptor: None,
+                phenomena: vec![WeatherPhenomenon::Snow]
+            }
+        );
+        assert_eq!(
+            parse_present_weather("-SHRA")?.1,
+            PresentWeather {
+                intensity: WeatherIntensity::Light,
+                descriptor: Some(WeatherDescriptor::Showers),
+                phenomena: vec![WeatherPhenomenon::Rain]
+            }
+        );
+        assert_eq!(
+            parse_present_weather("VCTS")?.1,
+            PresentWeather {
+                intensity: WeatherIntensity::InVicinity,
+                descriptor: Some(WeatherDescriptor::Thunderstorm),
+                phenomena: vec![]
+            }
+        );
+        assert_eq!(
+            parse_present_weather("FZFG")?.1,
+            PresentWeather {
+                intensity: WeatherIntensity::Moderate,
+                descriptor: Some(WeatherDescriptor::Freezing),
+                phenomena: vec![WeatherPhenomenon::Fog]
+            }
+        );
+        assert_eq!(
+            parse_present_weather("BCFG")?.1,
+            PresentWeather {
+                intensity: WeatherIntensity::Moderate,
+                descriptor: Some(WeatherDescriptor::Patches),
+                phenomena: vec![WeatherPhenomenon::Fog]
+            }
+        );
+        assert_eq!(
+            parse_present_weather("+TSRAGS")?.1,
+            PresentWeather {
+                intensity: WeatherIntensity::Heavy,
+                descriptor: Some(WeatherDescriptor::Thunderstorm),
+                phenomena: vec![WeatherPhenomenon::Rain, WeatherPhenomenon::SmallHail]
+            }
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_present_weather_rejects_other_groups() {
+        assert!(parse_present_weather("BKN022").is_err());
+        assert!(parse_present_weather("M04/M07").is_err());
+        assert!(parse_present_weather("NOSIG").is_err());
+        assert!(parse_present_weather("-").is_err());
+        assert!(parse_present_weather("//////").is_err());
+        assert!(parse_present_weather("//////CB").is_err());
+        assert!(parse_present_weather("+//").is_err());
+    }
+
+    #[test]
+    fn test_check_present_weather_matches_parser() {
+        for sample in [
+            "+SN", "-SHRA", "VCTS", "FZFG", "BCFG", "//", "+TSRASN", "BKN022", "NOSIG", "-",
+            "//////", "//////CB", "+//", "RA//",
+        ] {
+            assert_eq!(
+                check_present_weather(sample).map(|(rest, _)| rest),
+                parse_present_weather(sample).map(|(rest, _)| rest),
+                "{}",
+                sample
+            );
+        }
+    }
+
+    #[test]
+    fn test_parse_not_observed_weather() -> anyhow::Result<()> {
+        let (rest, weather) = parse_weather(" // //////")?;
+        assert_eq!(rest, " //////");
+        assert_eq!(weather.len(), 1);
+        assert!(weather[0].is_not_observed());
+        assert_eq!(
+            weather[0].phenomena[0].category(),
+            PhenomenonCategory::Other
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_weather() -> anyhow::Result<()> {
+        let (rest, weather) = parse_weather(" -SHRA BR BKN022")?;
+        assert_eq!(rest, " BKN022");
+        assert_eq!(
+            weather,
+            vec![
+                PresentWeather {
+                    intensity: WeatherIntensity::Light,
+                    descriptor: Some(WeatherDescriptor::Showers),
+                    phenomena: vec![WeatherPhenomenon::Rain]
+                },
+                PresentWeather {
+                    intensity: WeatherIntensity::Moderate,
+                    descriptor: None,
+                    phenomena: vec![WeatherPhenomenon::Mist]
+                }
+            ]
+        );
+        assert_eq!(
+            WeatherPhenomenon::Mist.category(),
+            PhenomenonCategory::Obscuration
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_display_present_weather() -> anyhow::Result<()> {
+        for sample in ["+SN", "-SHRASN", "VCTS", "BR", "FZDZ", "//", "+TSRAGR"] {
+            assert_eq!(parse_present_weather(sample)?.1.to_string(), sample);
+        }
+        Ok(())
+    }
+}
@@ -1,78 +1,165 @@
+use std::fmt;
 use std::str::FromStr;
 
-use nom::bytes::complete::{tag, take_while};
-use nom::character::{is_alphabetic, is_digit};
-use nom::sequence::tuple;
-use nom::{bytes::complete::take, IResult};
+use nom::branch::alt;
+use nom::bytes::complete::{tag, tag_no_case, take_while_m_n};
+use nom::character::complete::multispace1;
+use nom::character::is_digit;
+use nom::combinator::{map_opt, map_res, opt, value};
+use nom::error::ErrorKind;
+use nom::sequence::{preceded, separated_pair, terminated, tuple};
+use nom::IResult;
 
-fn parse_v(s: &str) -> IResult<&str, &str> {
-    tag("V")(s)
+use crate::error::{MetarElement, MetarError};
+use crate::{code, decode, end_of_group, Code};
+
+fn digits(min: usize, max: usize) -> impl FnMut(&str) -> IResult<&str, &str> {
+    move |s| take_while_m_n(min, max, |x: char| is_digit(x as u8))(s)
 }
 
-fn parse_variable_wind_direction(s: &str) -> Result<(&str, Option<(u16, u16)>), anyhow::Error> {
-    let components = tuple((
-        take_while(|x: char| is_digit(x as u8)),
-        parse_v,
-        take_while(|x: char| is_digit(x as u8)),
-    ))(s.trim());
+fn parse_direction(s: &str) -> IResult<&str, WindDirection> {
+    alt((
+        value(WindDirection::Variable, tag("VRB")),
+        value(WindDirection::Missing, tag("///")),
+        map_opt(digits(3, 3), code),
+    ))(s)
+}
 
-    match components {
-        Ok((rest, (d1, _, d2))) => Ok((rest, Some((d1.parse::<u16>()?, d2.parse::<u16>()?)))),
-        Err(_) => Ok((s, None)),
-    }
+fn parse_speed(s: &str) -> IResult<&str, WindSpeed> {
+    alt((
+        value(WindSpeed::Missing, tag("//")),
+        map_res(preceded(tag("P"), digits(2, 3)), |speed: &str| {
+            speed.parse().map(WindSpeed::AboveMaximum)
+        }),
+        map_res(digits(2, 3), |speed: &str| {
+            speed.parse().map(WindSpeed::Exact)
+        }),
+    ))(s)
+}
+
+fn parse_unit(s: &str) -> IResult<&str, WindUnit> {
+    map_opt(
+        alt((
+            tag_no_case("KT"),
+            tag_no_case("MPS"),
+            tag_no_case("KMH"),
+            tag_no_case("MPH"),
+        )),
+        code,
+    )(s)
+}
+
+/// Extreme directions of a variable wind, e.g. `180V250`. Older reports drop
+/// the leading zero, as in `20V40`.
+fn parse_variable_sector(s: &str) -> IResult<&str, (u16, u16)> {
+    let bearing = || map_res(digits(2, 3), str::parse::<u16>);
+    preceded(
+        multispace1,
+        terminated(separated_pair(bearing(), tag("V"), bearing()), end_of_group),
+    )(s)
 }
 
 pub fn parse_wind(s: &str) -> IResult<&str, Wind> {
     let s = s.trim_start();
-    let (rest, direction) = take(3usize)(s)?;
-    let (rest, speed) = take_while(|x: char| is_digit(x as u8))(rest)?;
-    let gust_speed: Option<&str> = None;
-
-    let (rest, gust_speed) = if &rest[..1] == "G" {
-        // Gusting
-        let (rest, gust_speed) = take_while(|x: char| is_digit(x as u8))(&rest[1..])?;
-        (rest, Some(gust_speed))
-    } else {
-        (rest, gust_speed)
-    };
-
-    let (rest, unit) = take_while(|x: char| is_alphabetic(x as u8))(rest)?;
-    let (rest, variable_components) = parse_variable_wind_direction(rest).unwrap();
+    let (rest, (direction, speed, gust_speed, unit)) = terminated(
+        tuple((
+            parse_direction,
+            parse_speed,
+            opt(preceded(tag("G"), parse_speed)),
+            parse_unit,
+        )),
+        end_of_group,
+    )(s)?;
+    let (rest, variable_direction) = opt(parse_variable_sector)(rest)?;
 
-    let w = Wind::from_str(direction, speed, gust_speed, unit, variable_components).unwrap();
+    let direction = match (direction, speed) {
+        (WindDirection::Direct(0), WindSpeed::Exact(0)) => WindDirection::Calm,
+        (direction, _) => direction,
+    };
+    let w = Wind::new(direction, speed, gust_speed, unit, variable_direction)
+        .ok_or_else(|| nom::Err::Error(nom::error::Error::new(s, ErrorKind::Verify)))?;
     Ok((rest, w))
 }
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, PartialEq, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
 pub enum WindUnit {
     Mps,
     Mph,
     Kt,
+    /// Kilometres per hour, only found in legacy reports.
+    Kmh,
 }
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, PartialEq, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(
+    feature = "serde",
+    serde(tag = "type", content = "value", rename_all = "snake_case")
+)]
 pub enum WindDirection {
     Direct(u16),
     Variable,
+    /// `00000KT`.
+    Calm,
+    /// `///`: direction not available.
+    Missing,
+}
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(
+    feature = "serde",
+    serde(tag = "type", content = "value", rename_all = "snake_case")
+)]
+pub enum WindSpeed {
+    Exact(u16),
+    /// `P99`: the speed is above the maximum the station can report.
+    AboveMaximum(u16),
+    /// `//`: speed not available.
+    Missing,
 }
 
-#[derive(Debug, PartialEq)]
+impl WindSpeed {
+    /// Reported value, the limit for `AboveMaximum`.
+    pub fn value(&self) -> Option<u16> {
+        match self {
+            WindSpeed::Exact(speed) | WindSpeed::AboveMaximum(speed) => Some(*speed),
+            WindSpeed::Missing => None,
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Wind {
     pub direction: WindDirection,
-    pub speed: u16,
-    pub gust_speed: Option<u16>,
+    pub speed: WindSpeed,
+    pub gust_speed: Option<WindSpeed>,
     pub unit: WindUnit,
     pub variable_direction: Option<(u16, u16)>,
 }
 impl Wind {
+    /// `None` when the direction or the variable sector is beyond 360 degrees.
     fn new(
         direction: WindDirection,
-        speed: u16,
-        gust_speed: Option<u16>,
+        speed: WindSpeed,
+        gust_speed: Option<WindSpeed>,
         unit: WindUnit,
         variable_direction: Option<(u16, u16)>,
-    ) -> anyhow::Result<Wind> {
-        Ok(Wind {
+    ) -> Option<Wind> {
+        if let WindDirection::Direct(direction) = direction {
+            if direction > 360 {
+                return None;
+            }
+        }
+        if let Some((from, to)) = variable_direction {
+            if from > 360 || to > 360 {
+                return None;
+            }
+        }
+        Some(Wind {
             direction,
             speed,
             gust_speed,
@@ -81,51 +168,106 @@ impl Wind {
         })
     }
 
-    fn from_str(
-        direction: &str,
-        speed: &str,
-        gust_speed: Option<&str>,
-        unit: &str,
-        variable_direction: Option<(u16, u16)>,
-    ) -> anyhow::Result<Wind> {
-        let direction: WindDirection = direction.parse()?;
-        let gust_speed = gust_speed.and_then(|s| s.parse::<u16>().ok());
-        let speed: u16 = speed.parse()?;
-        let unit: WindUnit = unit.parse()?;
+    pub fn is_calm(&self) -> bool {
+        self.direction == WindDirection::Calm
+    }
+}
 
-        Wind::new(direction, speed, gust_speed, unit, variable_direction)
+impl Code for WindUnit {
+    const ELEMENT: MetarElement = MetarElement::Wind;
+    const EXPECTED: &'static str = "KT, MPS, KMH or MPH";
+
+    fn from_code(s: &str) -> Option<Self> {
+        [
+            ("MPS", WindUnit::Mps),
+            ("MPH", WindUnit::Mph),
+            ("KT", WindUnit::Kt),
+            ("KMH", WindUnit::Kmh),
+        ]
+        .into_iter()
+        .find(|(code, _)| code.eq_ignore_ascii_case(s))
+        .map(|(_, unit)| unit)
     }
 }
 
 impl FromStr for WindUnit {
-    type Err = anyhow::Error;
+    type Err = MetarError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        match s.to_uppercase().as_str() {
-            "MPS" => Ok(WindUnit::Mps),
-            "MPH" => Ok(WindUnit::Mph),
-            "KT" => Ok(WindUnit::Kt),
-            _ => Err(anyhow::Error::msg("Not a WindUnit")),
+        decode(s)
+    }
+}
+
+impl Code for WindDirection {
+    const ELEMENT: MetarElement = MetarElement::Wind;
+    const EXPECTED: &'static str = "direction in degrees, VRB or ///";
+
+    fn from_code(s: &str) -> Option<Self> {
+        match s.parse::<u16>() {
+            Ok(num) => Some(WindDirection::Direct(num)),
+            Err(_) => match s {
+                "VRB" => Some(WindDirection::Variable),
+                "///" => Some(WindDirection::Missing),
+                _ => None,
+            },
         }
     }
 }
+
 impl FromStr for WindDirection {
-    type Err = anyhow::Error;
+    type Err = MetarError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        match s.parse::<u16>() {
-            Ok(num) => Ok(WindDirection::Direct(num)),
-            Err(_) => {
-                if s == "VRB" {
-                    Ok(WindDirection::Variable)
-                } else {
-                    Err(anyhow::Error::msg(format!(
-                        "{:?} Not a valid WindDirection",
-                        s
-                    )))
-                }
-            }
+        decode(s)
+    }
+}
+
+impl fmt::Display for WindUnit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let unit = match self {
+            WindUnit::Mps => "MPS",
+            WindUnit::Mph => "MPH",
+            WindUnit::Kt => "KT",
+            WindUnit::Kmh => "KMH",
+        };
+        f.write_str(unit)
+    }
+}
+
+impl fmt::Display for WindDirection {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            WindDirection::Direct(direction) => write!(f, "{:03}", direction),
+            WindDirection::Variable => f.write_str("VRB"),
+            WindDirection::Calm => f.write_str("000"),
+            WindDirection::Missing => f.write_str("///"),
+        }
+    }
+}
+
+impl fmt::Display for WindSpeed {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            WindSpeed::Exact(speed) => write!(f, "{:02}", speed),
+            WindSpeed::AboveMaximum(speed) => write!(f, "P{:02}", speed),
+            WindSpeed::Missing => f.write_str("//"),
+        }
+    }
+}
+
+/// Writes the wind group, followed by the variable sector, e.g.
+/// `22010G40KT 200V240`.
+impl fmt::Display for Wind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}{}", self.direction, self.speed)?;
+        if let Some(gust) = &self.gust_speed {
+            write!(f, "G{}", gust)?;
         }
+        write!(f, "{}", self.unit)?;
+        if let Some((from, to)) = self.variable_direction {
+            write!(f, " {:03}V{:03}", from, to)?;
+        }
+        Ok(())
     }
 }
 #[cfg(test)]
@@ -134,47 +276,60 @@ mod test {
     use super::*;
 
     #[test]
-    fn test_variable_wind() {
-        assert_eq!(
-            parse_variable_wind_direction("20V40").unwrap(),
-            (("", Some((20, 40))))
-        );
-
-        assert_eq!(
-            parse_variable_wind_direction("200V40").unwrap(),
-            (("", Some((200, 40))))
-        );
-        assert_eq!(
-            parse_variable_wind_direction("200V240").unwrap(),
-            (("", Some((200, 240))))
-        );
-        assert_eq!(
-            parse_variable_wind_direction("20V240").unwrap(),
-            (("", Some((20, 240))))
-        )
+    fn test_variable_wind() -> anyhow::Result<()> {
+        assert_eq!(parse_variable_sector(" 200V240")?, ("", (200, 240)));
+        assert_eq!(parse_variable_sector(" 020V040 9999")?, (" 9999", (20, 40)));
+        assert_eq!(parse_variable_sector(" 20V40")?, ("", (20, 40)));
+        assert_eq!(parse_variable_sector(" 200V40")?, ("", (200, 40)));
+        assert_eq!(parse_variable_sector(" 20V240")?, ("", (20, 240)));
+        assert!(parse_variable_sector(" 2V40").is_err());
+        assert!(parse_variable_sector(" 200V2400").is_err());
+        Ok(())
     }
+
     #[test]
     fn test_wind() {
         assert_eq!(
             parse_wind("22010KT").unwrap().1,
-            Wind::new(WindDirection::Direct(220), 10, None, WindUnit::Kt, None).unwrap()
+            Wind::new(
+                WindDirection::Direct(220),
+                WindSpeed::Exact(10),
+                None,
+                WindUnit::Kt,
+                None
+            )
+            .unwrap()
         );
 
         assert_eq!(
             parse_wind("220100MPS").unwrap().1,
-            Wind::new(WindDirection::Direct(220), 100, None, WindUnit::Mps, None).unwrap()
+            Wind::new(
+                WindDirection::Direct(220),
+                WindSpeed::Exact(100),
+                None,
+                WindUnit::Mps,
+                None
+            )
+            .unwrap()
         );
         assert_eq!(
             parse_wind("22010G40KT").unwrap().1,
-            Wind::new(WindDirection::Direct(220), 10, Some(40), WindUnit::Kt, None).unwrap()
+            Wind::new(
+                WindDirection::Direct(220),
+                WindSpeed::Exact(10),
+                Some(WindSpeed::Exact(40)),
+                WindUnit::Kt,
+                None
+            )
+            .unwrap()
         );
 
         assert_eq!(
             parse_wind("22010G40KT 200V240").unwrap().1,
             Wind::new(
                 WindDirection::Direct(220),
-                10,
-                Some(40),
+                WindSpeed::Exact(10),
+                Some(WindSpeed::Exact(40)),
                 WindUnit::Kt,
                 Some((200, 240))
             )
@@ -183,7 +338,79 @@ mod test {
 
         assert_eq!(
             parse_wind("VRB11G40KT").unwrap().1,
-            Wind::new(WindDirection::Variable, 11, Some(40), WindUnit::Kt, None).unwrap()
+            Wind::new(
+                WindDirection::Variable,
+                WindSpeed::Exact(11),
+                Some(WindSpeed::Exact(40)),
+                WindUnit::Kt,
+                None
+            )
+            .unwrap()
         )
     }
+
+    #[test]
+    fn test_display_wind() -> anyhow::Result<()> {
+        for sample in [
+            "22010G40KT 200V240",
+            "00000KT",
+            "VRB03KT",
+            "/////KT",
+            "250115G130MPS",
+            "24050GP99KT",
+            "18020KMH",
+        ] {
+            assert_eq!(parse_wind(sample)?.1.to_string(), sample);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_wind_rejects_other_groups() {
+        assert!(parse_wind("5000").is_err());
+        assert!(parse_wind("FEW020").is_err());
+        assert!(parse_wind("").is_err());
+        assert!(parse_wind("2").is_err());
+        assert!(parse_wind("22010").is_err());
+        assert!(parse_wind("22010KTS").is_err());
+        assert!(parse_wind("40010KT").is_err());
+        assert!(parse_wind("220100000KT").is_err());
+    }
+
+    #[test]
+    fn test_wind_edge_cases() -> anyhow::Result<()> {
+        let wind = parse_wind("00000KT")?.1;
+        assert!(wind.is_calm());
+        assert_eq!(wind.speed, WindSpeed::Exact(0));
+
+        let wind = parse_wind("/////KT")?.1;
+        assert_eq!(wind.direction, WindDirection::Missing);
+        assert_eq!(wind.speed, WindSpeed::Missing);
+        assert_eq!(wind.speed.value(), None);
+
+        let wind = parse_wind("25115G130KT")?.1;
+        assert_eq!(wind.direction, WindDirection::Direct(251));
+        assert_eq!(wind.speed, WindSpeed::Exact(15));
+        assert_eq!(wind.gust_speed, Some(WindSpeed::Exact(130)));
+
+        let wind = parse_wind("250115G130KT")?.1;
+        assert_eq!(wind.speed, WindSpeed::Exact(115));
+        assert_eq!(wind.gust_speed, Some(WindSpeed::Exact(130)));
+
+        let wind = parse_wind("270P99KT")?.1;
+        assert_eq!(wind.speed, WindSpeed::AboveMaximum(99));
+        assert_eq!(wind.speed.value(), Some(99));
+
+        let wind = parse_wind("24050GP99KT")?.1;
+        assert_eq!(wind.gust_speed, Some(WindSpeed::AboveMaximum(99)));
+
+        let wind = parse_wind("18020KMH 150V210")?.1;
+        assert_eq!(wind.unit, WindUnit::Kmh);
+        assert_eq!(wind.variable_direction, Some((150, 210)));
+
+        let (rest, wind) = parse_wind("18005KT 9999")?;
+        assert_eq!(wind.variable_direction, None);
+        assert_eq!(rest, " 9999");
+        Ok(())
+    }
 }
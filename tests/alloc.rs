@@ -0,0 +1,57 @@
+//! Allocation checks for [`MetarRef::parse`]. They live in their own test
+//! binary so that the counting allocator only replaces the global allocator
+//! here, not for the library's unit tests.
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::cell::Cell;
+
+use metar_pars::borrowed::MetarRef;
+
+/// Counts allocations made by the current thread, so that tests running in
+/// parallel do not disturb each other.
+struct CountingAllocator;
+
+thread_local! {
+    static ALLOCATIONS: Cell<usize> = const { Cell::new(0) };
+}
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let _ = ALLOCATIONS.try_with(|count| count.set(count.get() + 1));
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+fn allocations<T>(f: impl FnOnce() -> T) -> (T, usize) {
+    let before = ALLOCATIONS.with(Cell::get);
+    let result = f();
+    (result, ALLOCATIONS.with(Cell::get) - before)
+}
+
+#[test]
+fn test_parse_does_not_allocate() {
+    for sample in [
+        "METAR LFPG 141630Z AUTO 22010KT 180V250 9999 R24/1200 R06/P2000 FEW020 BKN040 15/10 Q1013 NOSIG RMK AO2=",
+        "SPECI KJFK 141651Z COR AUTO 22010KT 1 1/2SM R04R/1000V1600FT FEW008 BKN020 12/11 A2992 RMK AO2 SLP132 $",
+        "SPECI KJFK 141651Z COR AUTO 22010KT 1 1/2SM R04R/1000V1600FT -RA BR FEW008 BKN020 12/11 A2992 RMK AO2 SLP132 $",
+        "METAR LICJ 141600Z 12012KT 3000 +TSRASN VCSH FZFG // BKN022 M04/M07 Q1020 NOSIG",
+    ] {
+        let (metar, count) = allocations(|| MetarRef::parse(sample));
+        assert!(metar.is_ok());
+        assert_eq!(count, 0, "{}", sample);
+    }
+
+    // Only the token of the returned error is allocated.
+    for sample in ["LFPG 141630Z 220X0KT 9999", "LFPG 141630Z 22010KT 9999 XX1"] {
+        let (error, count) = allocations(|| MetarRef::parse(sample));
+        assert!(error.is_err());
+        assert_eq!(count, 1, "{}", sample);
+    }
+}